@@ -0,0 +1,144 @@
+use crate::report::{Record, Status};
+use std::{collections::HashMap, fmt, fs, path::Path};
+use toml::{Table, Value};
+
+/// Known answers, read from a TOML file with a `[dayN]` table per day holding
+/// `part1` and `part2` as integers or strings.
+#[derive(Debug, Default)]
+pub struct Answers(HashMap<(u8, u8), String>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::Pass => "PASS",
+            Verdict::Fail => "FAIL",
+            Verdict::Unknown => "UNKNOWN",
+        })
+    }
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+        Answers::parse(&text).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| err.message().to_string())?;
+        let mut answers = HashMap::new();
+        for (key, parts) in table {
+            let Some(Ok(day @ 1..=25)) = key.strip_prefix("day").map(str::parse) else {
+                return Err(format!(
+                    "expected a table named day1 to day25, found '{key}'"
+                ));
+            };
+            let Value::Table(parts) = parts else {
+                return Err(format!("expected '{key}' to be a table"));
+            };
+            for (name, answer) in parts {
+                let part = match name.as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    _ => {
+                        return Err(format!(
+                            "expected part1 or part2 in '{key}', found '{name}'"
+                        ))
+                    }
+                };
+                let answer = match answer {
+                    Value::Integer(answer) => answer.to_string(),
+                    Value::String(answer) => answer,
+                    _ => {
+                        return Err(format!(
+                            "expected '{key}.{name}' to be an integer or a string"
+                        ))
+                    }
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+        Ok(Answers(answers))
+    }
+
+    pub fn expected(&self, day: u8, part: u8) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+
+    /// Sets the verdict of `record`. Errors and missing answers fail, while
+    /// an answer is only unknown when there is nothing to compare it with.
+    pub fn check(&self, record: &mut Record) {
+        let expected = self.expected(record.day, record.part);
+        record.verdict = Some(match (&record.status, expected) {
+            (Status::Ok(answer), Some(expected)) if answer == expected => Verdict::Pass,
+            (_, Some(_)) | (Status::Error(_), None) => Verdict::Fail,
+            (_, None) => Verdict::Unknown,
+        });
+        record.expected = expected.map(str::to_string);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Answers, Verdict};
+    use crate::report::{Record, Status};
+    use std::time::Duration;
+
+    #[test]
+    fn integers_and_strings() {
+        let answers = Answers::parse(
+            "[day1]\npart1 = 69912\n\n[day5]\npart2 = \"RBTWJWMCF\"\n\
+             [day10]\npart2 = \"\"\"\n#..#\n.##.\"\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(answers.expected(1, 1), Some("69912"));
+        assert_eq!(answers.expected(1, 2), None);
+        assert_eq!(answers.expected(5, 2), Some("RBTWJWMCF"));
+        assert_eq!(answers.expected(10, 2), Some("#..#\n.##."));
+    }
+
+    #[test]
+    fn verdicts() {
+        let answers = Answers::parse("[day1]\npart1 = 69912\n").unwrap();
+        let verdict = |part, status| {
+            let mut record = Record {
+                day: 1,
+                part,
+                status,
+                parse: Duration::ZERO,
+                elapsed: Duration::ZERO,
+                verdict: None,
+                expected: None,
+            };
+            answers.check(&mut record);
+            record.verdict.unwrap()
+        };
+        assert_eq!(verdict(1, Status::Ok("69912".to_string())), Verdict::Pass);
+        assert_eq!(verdict(1, Status::Ok("69913".to_string())), Verdict::Fail);
+        assert_eq!(verdict(1, Status::NotImplemented), Verdict::Fail);
+        assert_eq!(verdict(2, Status::Ok("1".to_string())), Verdict::Unknown);
+        assert_eq!(verdict(2, Status::NotImplemented), Verdict::Unknown);
+        assert_eq!(verdict(2, Status::Error("oops".to_string())), Verdict::Fail);
+    }
+
+    #[test]
+    fn unexpected_keys() {
+        assert_eq!(
+            Answers::parse("[day26]\npart1 = 1\n").unwrap_err(),
+            "expected a table named day1 to day25, found 'day26'"
+        );
+        assert_eq!(
+            Answers::parse("[day2]\npart3 = 1\n").unwrap_err(),
+            "expected part1 or part2 in 'day2', found 'part3'"
+        );
+    }
+}
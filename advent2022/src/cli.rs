@@ -0,0 +1,264 @@
+use std::{fmt, path::PathBuf};
+
+pub const USAGE: &str = "\
+Usage: advent2022 [OPTIONS] [DAYS]
+
+Solves the puzzles of DAYS, e.g. `3,5,10-12`, or of every day.
+
+Options:
+  -p, --part <PART>      Solve only part 1 or part 2
+  -i, --input <PATH>     Directory with dayN.txt files [default: input], or the
+                         input file of a single day. `-` reads from stdin
+  -f, --format <FORMAT>  Output format: text, brief, json or csv [default: text]
+  -j, --jobs <N>         Solve days and parts on N threads, 0 for one per CPU
+                         [default: 1]
+  -a, --answers <PATH>   Check the answers against a TOML file with a [dayN]
+                         table per day holding part1 and part2
+      --dot <PATH>       Write the day 16 valves as Graphviz DOT, with the
+                         route of part 1, or of part 2 with --part 2
+      --dot-graph <GRAPH>
+                         Valves to write: raw or compressed [default: raw]
+      --explain          Print the day 19 robots built with each blueprint in
+                         24 minutes, or with the first three in 32 minutes
+                         with --part 2
+  -h, --help             Print this message
+";
+
+/// Where the puzzle input comes from.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Source {
+    Dir(PathBuf),
+    File(PathBuf),
+    Stdin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Answers with headings and timings.
+    Text,
+    /// One line per answer.
+    Brief,
+    /// An array of records with status, answer and elapsed nanoseconds.
+    Json,
+    /// The same records as comma separated values.
+    Csv,
+}
+
+/// Which valves of day 16 to write with `--dot`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DotGraph {
+    /// Every valve and tunnel of the input.
+    Raw,
+    /// The valves with flow and the distances between them.
+    Compressed,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    /// Selected days in calendar order, empty for every day.
+    pub days: Vec<u8>,
+    /// Selected part, `None` for both.
+    pub part: Option<u8>,
+    pub source: Source,
+    pub format: Format,
+    /// Number of threads, 0 for one per CPU.
+    pub jobs: usize,
+    /// File with known answers to check against.
+    pub answers: Option<PathBuf>,
+    /// File to write the day 16 valves to.
+    pub dot: Option<PathBuf>,
+    pub dot_graph: DotGraph,
+    /// Whether to report the day 19 build orders.
+    pub explain: bool,
+    pub help: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn usage_error<T>(message: impl Into<String>) -> Result<T, UsageError> {
+    Err(UsageError(message.into()))
+}
+
+impl Options {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, UsageError> {
+        let mut options = Options {
+            days: Vec::new(),
+            part: None,
+            source: Source::Dir(PathBuf::from("input")),
+            format: Format::Text,
+            jobs: 1,
+            answers: None,
+            dot: None,
+            dot_graph: DotGraph::Raw,
+            explain: false,
+            help: false,
+        };
+        let mut input = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| UsageError(format!("{flag} needs a value")))
+            };
+            match flag {
+                "-h" | "--help" => options.help = true,
+                "-p" | "--part" => match value()?.as_str() {
+                    "1" => options.part = Some(1),
+                    "2" => options.part = Some(2),
+                    part => return usage_error(format!("invalid part '{part}', expected 1 or 2")),
+                },
+                "-i" | "--input" => input = Some(value()?),
+                "-j" | "--jobs" => match value()?.parse() {
+                    Ok(jobs) => options.jobs = jobs,
+                    Err(_) => return usage_error("invalid number of jobs"),
+                },
+                "-a" | "--answers" => options.answers = Some(PathBuf::from(value()?)),
+                "--dot" => options.dot = Some(PathBuf::from(value()?)),
+                "--dot-graph" => match value()?.as_str() {
+                    "raw" => options.dot_graph = DotGraph::Raw,
+                    "compressed" => options.dot_graph = DotGraph::Compressed,
+                    graph => return usage_error(format!("unknown graph '{graph}'")),
+                },
+                "--explain" => options.explain = true,
+                "-f" | "--format" => match value()?.as_str() {
+                    "text" => options.format = Format::Text,
+                    "brief" => options.format = Format::Brief,
+                    "json" => options.format = Format::Json,
+                    "csv" => options.format = Format::Csv,
+                    format => return usage_error(format!("unknown format '{format}'")),
+                },
+                flag if flag.starts_with('-') => {
+                    return usage_error(format!("unknown option '{flag}'"))
+                }
+                days => options
+                    .days
+                    .extend(aoc2022::parse_days(days).map_err(UsageError)?),
+            }
+        }
+        options.days.sort_unstable();
+        options.days.dedup();
+        if let Some(input) = input {
+            options.source = match PathBuf::from(input) {
+                path if path.as_os_str() == "-" => Source::Stdin,
+                path if path.is_dir() => Source::Dir(path),
+                path => Source::File(path),
+            };
+            if !matches!(options.source, Source::Dir(_)) && options.days.len() != 1 {
+                return usage_error("an input file or stdin needs exactly one day");
+            }
+        }
+        if options.dot.is_some() {
+            if !options.days.is_empty() && !options.days.contains(&16) {
+                return usage_error("--dot needs day 16");
+            }
+            if options.source == Source::Stdin {
+                return usage_error("--dot cannot read the input from stdin");
+            }
+        }
+        if options.explain {
+            if !options.days.is_empty() && !options.days.contains(&19) {
+                return usage_error("--explain needs day 19");
+            }
+            if options.source == Source::Stdin {
+                return usage_error("--explain cannot read the input from stdin");
+            }
+            if !matches!(options.format, Format::Text | Format::Brief) {
+                return usage_error("--explain needs text or brief output");
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DotGraph, Format, Options, Source};
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(ToString::to_string)).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn defaults() {
+        let options = parse(&[]).unwrap();
+        assert!(options.days.is_empty());
+        assert_eq!(options.part, None);
+        assert_eq!(options.source, Source::Dir(PathBuf::from("input")));
+        assert_eq!(options.format, Format::Text);
+        assert_eq!(options.jobs, 1);
+        assert_eq!(options.dot, None);
+        assert_eq!(options.dot_graph, DotGraph::Raw);
+        assert!(!options.explain);
+    }
+
+    #[test]
+    fn days_and_ranges() {
+        let args = [
+            "10-12,3", "5", "--part=2", "-f", "brief", "-a", "a.toml", "-j", "0",
+        ];
+        let options = parse(&args).unwrap();
+        assert_eq!(options.days, [3, 5, 10, 11, 12]);
+        assert_eq!(options.part, Some(2));
+        assert_eq!(options.format, Format::Brief);
+        assert_eq!(options.answers, Some(PathBuf::from("a.toml")));
+        assert_eq!(options.jobs, 0);
+    }
+
+    #[test]
+    fn stdin_needs_one_day() {
+        assert_eq!(parse(&["7", "-i", "-"]).unwrap().source, Source::Stdin);
+        assert_eq!(
+            parse(&["7,8", "-i", "-"]).unwrap_err(),
+            "an input file or stdin needs exactly one day"
+        );
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(
+            parse(&["26"]).unwrap_err(),
+            "invalid day '26', expected 1 to 25"
+        );
+        assert_eq!(parse(&["5-3"]).unwrap_err(), "invalid range '5-3'");
+        assert_eq!(parse(&["-f", "xml"]).unwrap_err(), "unknown format 'xml'");
+        assert_eq!(
+            parse(&["-p", "3"]).unwrap_err(),
+            "invalid part '3', expected 1 or 2"
+        );
+        assert_eq!(parse(&["--input"]).unwrap_err(), "--input needs a value");
+        assert_eq!(
+            parse(&["--dot-graph", "simple"]).unwrap_err(),
+            "unknown graph 'simple'"
+        );
+        assert_eq!(
+            parse(&["15", "--dot", "v.dot"]).unwrap_err(),
+            "--dot needs day 16"
+        );
+        assert_eq!(
+            parse(&["15", "--explain"]).unwrap_err(),
+            "--explain needs day 19"
+        );
+        assert_eq!(
+            parse(&["19", "--explain", "-f", "json"]).unwrap_err(),
+            "--explain needs text or brief output"
+        );
+        assert_eq!(
+            parse(&["--verbose"]).unwrap_err(),
+            "unknown option '--verbose'"
+        );
+    }
+}
@@ -1,177 +1,265 @@
 #![warn(clippy::pedantic)]
-#![allow(clippy::too_many_lines)]
 
-use lazy_st::{lazy, Lazy};
+mod answers;
+mod cli;
+mod report;
+
+use answers::{Answers, Verdict};
+use aoc2022::{
+    day16::{self, Graph, ValveProblem},
+    day19, Parsed, Puzzle,
+};
+use cli::{DotGraph, Format, Options, Source};
+use rayon::prelude::*;
+use report::{Record, Status};
 use std::env;
-use std::fmt;
-use std::time::Instant;
+use std::fs;
+use std::io;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-struct Day {
-    no: i8,
-    part1: Lazy<String>,
-    part2: Lazy<String>,
+fn read(puzzle: Puzzle, source: &Source) -> io::Result<String> {
+    match source {
+        Source::Dir(dir) => fs::read_to_string(dir.join(format!("day{}.txt", puzzle.day))),
+        Source::File(path) => fs::read_to_string(path),
+        Source::Stdin => io::read_to_string(io::stdin().lock()),
+    }
 }
 
-impl std::fmt::Display for Day {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "=== Day {} ===", self.no)?;
-        let now = Instant::now();
-        writeln!(f, "Part 1: {}", *self.part1)?;
-        writeln!(f, "  finished in {:.2?}", now.elapsed())?;
-        let now = Instant::now();
-        writeln!(f, "Part 2: {}", *self.part2)?;
-        writeln!(f, "  finished in {:.2?}", now.elapsed())?;
-        writeln!(f)
+fn solve_part(puzzle: Puzzle, parsed: &dyn Parsed, parse: Duration, part: u8) -> Record {
+    let now = Instant::now();
+    let answer = if part == 1 {
+        parsed.part1()
+    } else {
+        parsed.part2()
+    };
+    Record {
+        day: puzzle.day,
+        part,
+        status: match answer {
+            Ok(Some(answer)) => Status::Ok(answer),
+            Ok(None) => Status::NotImplemented,
+            Err(err) => Status::Error(format!("day {} part {part}: {err}", puzzle.day)),
+        },
+        parse,
+        elapsed: now.elapsed(),
+        verdict: None,
+        expected: None,
     }
 }
 
-fn main() {
-    let days = vec![
-        Day {
-            no: 1,
-            part1: lazy!(aoc2022::day1::most_calories_from_file("input/day1.txt").to_string()),
-            part2: lazy!(
-                aoc2022::day1::three_most_calories_from_file("input/day1.txt").to_string()
-            ),
-        },
-        Day {
-            no: 2,
-            part1: lazy!(aoc2022::day2::score_selected("input/day2.txt").to_string()),
-            part2: lazy!(aoc2022::day2::score_end("input/day2.txt").to_string()),
-        },
-        Day {
-            no: 3,
-            part1: lazy!(aoc2022::day3::rucksack("input/day3.txt").to_string()),
-            part2: lazy!(aoc2022::day3::badges("input/day3.txt").to_string()),
-        },
-        Day {
-            no: 4,
-            part1: lazy!(aoc2022::day4::fully_contain("input/day4.txt").to_string()),
-            part2: lazy!(aoc2022::day4::overlap("input/day4.txt").to_string()),
-        },
-        Day {
-            no: 5,
-            part1: lazy!(aoc2022::day5::crates_single("input/day5.txt")),
-            part2: lazy!(aoc2022::day5::crates_multiple("input/day5.txt")),
-        },
-        Day {
-            no: 6,
-            part1: lazy!(aoc2022::day6::start_of_package("input/day6.txt").to_string()),
-            part2: lazy!(aoc2022::day6::start_of_message("input/day6.txt").to_string()),
-        },
-        Day {
-            no: 7,
-            part1: lazy!(aoc2022::day7::sum_dirs_below("input/day7.txt").to_string()),
-            part2: lazy!(aoc2022::day7::dir_to_delete("input/day7.txt").to_string()),
-        },
-        Day {
-            no: 8,
-            part1: lazy!(aoc2022::day8::visible("input/day8.txt").to_string()),
-            part2: lazy!(aoc2022::day8::highest_score("input/day8.txt").to_string()),
-        },
-        Day {
-            no: 9,
-            part1: lazy!(aoc2022::day9::visited_nodes("input/day9.txt", 2).to_string()),
-            part2: lazy!(aoc2022::day9::visited_nodes("input/day9.txt", 10).to_string()),
-        },
-        Day {
-            no: 10,
-            part1: lazy!(aoc2022::day10::signal_strength("input/day10.txt").to_string()),
-            part2: lazy!(aoc2022::day10::crt("input/day10.txt")),
-        },
-        Day {
-            no: 11,
-            part1: lazy!(aoc2022::day11::monkey_business("input/day11.txt", true).to_string()),
-            part2: lazy!(aoc2022::day11::monkey_business("input/day11.txt", false).to_string()),
-        },
-        Day {
-            no: 12,
-            part1: lazy!(aoc2022::day12::shortest_path("input/day12.txt").to_string()),
-            part2: lazy!(aoc2022::day12::shortest_path_from_any_a("input/day12.txt").to_string()),
-        },
-        Day {
-            no: 13,
-            part1: lazy!(aoc2022::day13::sum_indices("input/day13.txt").to_string()),
-            part2: lazy!(aoc2022::day13::decoder_key("input/day13.txt").to_string()),
-        },
-        Day {
-            no: 14,
-            part1: lazy!(aoc2022::day14::sand_before_abyss("input/day14.txt").to_string()),
-            part2: lazy!(aoc2022::day14::sand_to_rest("input/day14.txt").to_string()),
-        },
-        Day {
-            no: 15,
-            part1: lazy!(aoc2022::day15::cannot_contain("input/day15.txt", 2_000_000).to_string()),
-            part2: lazy!(
-                aoc2022::day15::tuning_frequency("input/day15.txt", 4_000_000).to_string()
-            ),
-        },
-        Day {
-            no: 16,
-            part1: lazy!(aoc2022::day16::max_pressure("input/day16.txt").to_string()),
-            // part2: lazy!(aoc2022::day16::max_pressure_with_elephant("input/day16.txt").to_string()),
-            part2: lazy!(String::new()),
-        },
-        Day {
-            no: 17,
-            part1: lazy!(String::new()),
-            part2: lazy!(String::new()),
-        },
-        Day {
-            no: 18,
-            part1: lazy!(aoc2022::day18::surface_area("input/day18.txt").to_string()),
-            part2: lazy!(aoc2022::day18::external_surface_area("input/day18.txt").to_string()),
-        },
-        Day {
-            no: 19,
-            part1: lazy!(String::new()),
-            part2: lazy!(String::new()),
-        },
-        Day {
-            no: 20,
-            part1: lazy!(String::new()),
-            part2: lazy!(String::new()),
-        },
-        Day {
-            no: 21,
-            part1: lazy!(aoc2022::day21::monkey_yell("input/day21.txt").to_string()),
-            part2: lazy!(aoc2022::day21::equality_test("input/day21.txt").to_string()),
-        },
-        Day {
-            no: 22,
-            part1: lazy!(aoc2022::day22::final_password("input/day22.txt", 0).to_string()),
-            part2: lazy!(aoc2022::day22::final_password("input/day22.txt", 2).to_string()),
-        },
-        Day {
-            no: 23,
-            part1: lazy!(aoc2022::day23::empty_tiles("input/day23.txt").to_string()),
-            part2: lazy!(aoc2022::day23::no_move_round("input/day23.txt").to_string()),
-        },
-        Day {
-            no: 24,
-            part1: lazy!(aoc2022::day24::minutes("input/day24.txt", 1).to_string()),
-            part2: lazy!(aoc2022::day24::minutes("input/day24.txt", 3).to_string()),
-        },
-        Day {
-            no: 25,
-            part1: lazy!(aoc2022::day25::sum_snafu("input/day25.txt")),
-            part2: lazy!(String::new()),
-        },
-    ];
+fn failed(puzzle: Puzzle, parts: &[u8], message: &str, parse: Duration) -> Vec<Record> {
+    parts
+        .iter()
+        .map(|&part| Record {
+            day: puzzle.day,
+            part,
+            status: Status::Error(message.to_string()),
+            parse,
+            elapsed: Duration::ZERO,
+            verdict: None,
+            expected: None,
+        })
+        .collect()
+}
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 1 {
-        let idx = args[1].parse::<usize>().unwrap();
-        if 0 < idx && idx <= days.len() {
-            print!("{}", &days[idx - 1]);
-        } else {
-            println!("Day not implemented: {}", idx);
+/// Parses the input of a single day once, then solves the selected parts in
+/// `parallel` on the current thread pool or one after the other. A day that
+/// cannot be loaded gives an error record for each part, so that the
+/// remaining days still get their turn.
+fn solve(puzzle: Puzzle, options: &Options, parallel: bool) -> Vec<Record> {
+    let parts: Vec<u8> = [1, 2]
+        .into_iter()
+        .filter(|&part| options.part.is_none_or(|selected| selected == part))
+        .collect();
+    let input = match read(puzzle, &options.source) {
+        Ok(input) => input,
+        Err(err) => {
+            let message = format!("cannot read input for day {}: {err}", puzzle.day);
+            return failed(puzzle, &parts, &message, Duration::ZERO);
         }
+    };
+    let now = Instant::now();
+    let parsed = puzzle.parse(&input);
+    let parse = now.elapsed();
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => return failed(puzzle, &parts, &err.to_string(), parse),
+    };
+    if parallel {
+        parts
+            .into_par_iter()
+            .map(|part| solve_part(puzzle, &*parsed, parse, part))
+            .collect()
     } else {
-        let now = Instant::now();
-        for day in &days {
-            print!("{}", day);
+        parts
+            .into_iter()
+            .map(|part| solve_part(puzzle, &*parsed, parse, part))
+            .collect()
+    }
+}
+
+/// Writes the day 16 valves as DOT with the best route of the selected part.
+fn write_dot(options: &Options) -> Result<(), String> {
+    let Some(path) = &options.dot else {
+        return Ok(());
+    };
+    let puzzle = aoc2022::puzzle(16).unwrap();
+    let input = read(puzzle, &options.source)
+        .map_err(|err| format!("cannot read input for day 16: {err}"))?;
+    let valves = day16::parse(&input).map_err(|err| err.to_string())?;
+    let problem = if options.part == Some(2) {
+        ValveProblem::new(26, "AA", 2)
+    } else {
+        ValveProblem::new(30, "AA", 1)
+    };
+    let plan = problem.plan(&valves);
+    let dot = match options.dot_graph {
+        DotGraph::Raw => day16::dot(&valves, plan.as_ref()),
+        DotGraph::Compressed => Graph::new(&valves, "AA").unwrap().dot(plan.as_ref()),
+    };
+    fs::write(path, dot).map_err(|err| format!("cannot write {}: {err}", path.display()))
+}
+
+/// Prints the day 19 build orders of the selected part.
+fn explain(options: &Options) -> Result<(), String> {
+    if !options.explain {
+        return Ok(());
+    }
+    let puzzle = aoc2022::puzzle(19).unwrap();
+    let input = read(puzzle, &options.source)
+        .map_err(|err| format!("cannot read input for day 19: {err}"))?;
+    let blueprints = day19::parse(&input).map_err(|err| err.to_string())?;
+    print!("{}", day19::report(&blueprints, options.part.unwrap_or(1)));
+    Ok(())
+}
+
+/// Checks and prints the records of a single day as far as the format
+/// allows before all days are done.
+fn show(
+    day: u8,
+    mut records: Vec<Record>,
+    options: &Options,
+    answers: Option<&Answers>,
+) -> Vec<Record> {
+    if let Some(answers) = answers {
+        for record in &mut records {
+            answers.check(record);
+        }
+    }
+    match options.format {
+        Format::Text => print!("{}", report::text(day, &records)),
+        Format::Brief => print!("{}", report::brief(&records)),
+        Format::Json | Format::Csv => {}
+    }
+    if matches!(options.format, Format::Text | Format::Brief) {
+        for error in report::errors(&records) {
+            eprintln!("{error}");
+        }
+    }
+    if options.format == Format::Text {
+        println!();
+    }
+    records
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{}", cli::USAGE);
+            return ExitCode::from(2);
         }
-        println!("all finished in {:.2?}", now.elapsed());
+    };
+    if options.help {
+        print!("{}", cli::USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let answers = match options.answers.as_deref().map(Answers::load).transpose() {
+        Ok(answers) => answers,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    let puzzles = if options.days.is_empty() {
+        aoc2022::puzzles()
+    } else {
+        options
+            .days
+            .iter()
+            .filter_map(|&day| aoc2022::puzzle(day))
+            .collect()
+    };
+    let now = Instant::now();
+    let mut records = Vec::new();
+    if options.jobs == 1 {
+        for &puzzle in &puzzles {
+            let day = solve(puzzle, &options, false);
+            records.extend(show(puzzle.day, day, &options, answers.as_ref()));
+        }
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.jobs)
+            .build()
+            .expect("cannot start thread pool");
+        let days: Vec<Vec<Record>> = pool.install(|| {
+            puzzles
+                .par_iter()
+                .map(|&puzzle| solve(puzzle, &options, true))
+                .collect()
+        });
+        for (puzzle, day) in puzzles.iter().zip(days) {
+            records.extend(show(puzzle.day, day, &options, answers.as_ref()));
+        }
+    }
+    match options.format {
+        Format::Text if puzzles.len() > 1 => println!("all finished in {:.2?}", now.elapsed()),
+        Format::Json => print!("{}", report::json(&records)),
+        Format::Csv => print!("{}", report::csv(&records)),
+        _ => {}
+    }
+    if answers.is_some() && matches!(options.format, Format::Text | Format::Brief) {
+        println!("{}", report::summary(&records));
+    }
+    let dot = write_dot(&options);
+    if let Err(err) = &dot {
+        eprintln!("error: {err}");
+    }
+    let explained = explain(&options);
+    if let Err(err) = &explained {
+        eprintln!("error: {err}");
+    }
+    if dot.is_err()
+        || explained.is_err()
+        || records.iter().any(|record| {
+            matches!(record.status, Status::Error(_)) || record.verdict == Some(Verdict::Fail)
+        })
+    {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve_part;
+    use crate::report::Status;
+    use std::time::Duration;
+
+    #[test]
+    fn unsolvable_part_is_an_error() {
+        let puzzle = aoc2022::puzzle(21).unwrap();
+        let parsed = puzzle
+            .parse("root: a + b\na: humn * humn\nb: 4\nhumn: 1\n")
+            .unwrap();
+        // The error itself is tested with day 21.
+        let record = solve_part(puzzle, &*parsed, Duration::ZERO, 2);
+        assert!(
+            matches!(&record.status, Status::Error(message) if message.starts_with("day 21 part 2: ")),
+            "{:?}",
+            record.status
+        );
     }
 }
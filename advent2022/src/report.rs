@@ -0,0 +1,293 @@
+use crate::answers::Verdict;
+use std::{fmt::Write, time::Duration};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Ok(String),
+    NotImplemented,
+    Error(String),
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Ok(_) => "ok",
+            Status::NotImplemented => "not implemented",
+            Status::Error(_) => "error",
+        }
+    }
+}
+
+/// Outcome of one part of one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub status: Status,
+    /// Time taken to parse the input of the day, shared by both parts.
+    pub parse: Duration,
+    pub elapsed: Duration,
+    /// Set when checking against known answers.
+    pub verdict: Option<Verdict>,
+    pub expected: Option<String>,
+}
+
+impl Record {
+    fn answer(&self) -> &str {
+        match &self.status {
+            Status::Ok(answer) => answer,
+            _ => "",
+        }
+    }
+
+    fn error(&self) -> &str {
+        match &self.status {
+            Status::Error(message) => message,
+            _ => "",
+        }
+    }
+
+    fn verdict(&self) -> String {
+        match (self.verdict, &self.expected) {
+            (Some(Verdict::Fail), Some(expected)) => format!("FAIL, expected {expected}"),
+            (Some(verdict), _) => verdict.to_string(),
+            (None, _) => String::new(),
+        }
+    }
+}
+
+/// Number of records with each verdict, e.g. `PASS 41, FAIL 1, UNKNOWN 8`.
+pub fn summary(records: &[Record]) -> String {
+    [Verdict::Pass, Verdict::Fail, Verdict::Unknown]
+        .map(|verdict| {
+            let count = records
+                .iter()
+                .filter(|record| record.verdict == Some(verdict))
+                .count();
+            format!("{verdict} {count}")
+        })
+        .join(", ")
+}
+
+/// Distinct error messages among `records`, a failing day gives the same
+/// error for both parts.
+pub fn errors(records: &[Record]) -> Vec<&str> {
+    let mut errors: Vec<&str> = Vec::new();
+    for record in records {
+        let error = record.error();
+        if !error.is_empty() && !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+/// The records of a single day with a heading and timings. Errors are left
+/// out, see [`errors`].
+pub fn text(day: u8, records: &[Record]) -> String {
+    let mut out = format!("=== Day {day} ===\n");
+    if let Some(record) = records.iter().find(|record| record.error().is_empty()) {
+        writeln!(out, "Parse\n  finished in {:.2?}", record.parse).unwrap();
+    }
+    for record in records {
+        match &record.status {
+            Status::Ok(answer) => writeln!(out, "Part {}: {answer}", record.part).unwrap(),
+            Status::NotImplemented => {
+                writeln!(out, "Part {}: not implemented", record.part).unwrap();
+            }
+            Status::Error(_) => continue,
+        }
+        if record.verdict.is_some() {
+            writeln!(out, "  {}", record.verdict()).unwrap();
+        }
+        writeln!(out, "  finished in {:.2?}", record.elapsed).unwrap();
+    }
+    out
+}
+
+/// One line per answer, errors left out as for [`text`].
+pub fn brief(records: &[Record]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let answer = match &record.status {
+            Status::Ok(answer) => answer,
+            Status::NotImplemented => "not implemented",
+            Status::Error(_) => continue,
+        };
+        write!(out, "Day {} part {}: {answer}", record.day, record.part).unwrap();
+        if record.verdict.is_some() {
+            write!(out, " ({})", record.verdict()).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn csv(records: &[Record]) -> String {
+    let mut out =
+        String::from("day,part,status,answer,error,parse_ns,elapsed_ns,verdict,expected\n");
+    for record in records {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{}",
+            record.day,
+            record.part,
+            csv_field(record.status.name()),
+            csv_field(record.answer()),
+            csv_field(record.error()),
+            record.parse.as_nanos(),
+            record.elapsed.as_nanos(),
+            record
+                .verdict
+                .map(|verdict| verdict.to_string())
+                .unwrap_or_default(),
+            csv_field(record.expected.as_deref().unwrap_or_default())
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// An array of records with `answer` set for `ok` and `error` set for
+/// `error`, and `verdict` and `expected` when checking answers.
+pub fn json(records: &[Record]) -> String {
+    let mut out = String::from("[");
+    for (i, record) in records.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        write!(
+            out,
+            "  {{\"day\": {}, \"part\": {}, \"status\": {}",
+            record.day,
+            record.part,
+            json_string(record.status.name())
+        )
+        .unwrap();
+        match &record.status {
+            Status::Ok(answer) => write!(out, ", \"answer\": {}", json_string(answer)),
+            Status::Error(message) => write!(out, ", \"error\": {}", json_string(message)),
+            Status::NotImplemented => Ok(()),
+        }
+        .unwrap();
+        write!(
+            out,
+            ", \"parse_ns\": {}, \"elapsed_ns\": {}",
+            record.parse.as_nanos(),
+            record.elapsed.as_nanos()
+        )
+        .unwrap();
+        if let Some(verdict) = record.verdict {
+            write!(out, ", \"verdict\": \"{verdict}\"").unwrap();
+        }
+        if let Some(expected) = &record.expected {
+            write!(out, ", \"expected\": {}", json_string(expected)).unwrap();
+        }
+        out.push('}');
+    }
+    out.push_str("\n]\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv, errors, json, summary, Record, Status};
+    use crate::answers::Verdict;
+    use std::time::Duration;
+
+    fn records() -> Vec<Record> {
+        let record = |part, status| Record {
+            day: 10,
+            part,
+            status,
+            parse: Duration::from_nanos(700),
+            elapsed: Duration::from_nanos(1500),
+            verdict: None,
+            expected: None,
+        };
+        vec![
+            Record {
+                verdict: Some(Verdict::Fail),
+                expected: Some("13141".to_string()),
+                ..record(1, Status::Ok("13140".to_string()))
+            },
+            record(2, Status::Ok("##..\n#\"#.".to_string())),
+            record(1, Status::NotImplemented),
+            record(
+                1,
+                Status::Error("day 10, line 3: expected a number, found 'x'".to_string()),
+            ),
+            record(
+                2,
+                Status::Error("day 10, line 3: expected a number, found 'x'".to_string()),
+            ),
+        ]
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        assert_eq!(
+            csv(&records()),
+            "day,part,status,answer,error,parse_ns,elapsed_ns,verdict,expected\n\
+             10,1,ok,13140,,700,1500,FAIL,13141\n\
+             10,2,ok,\"##..\n#\"\"#.\",,700,1500,,\n\
+             10,1,not implemented,,,700,1500,,\n\
+             10,1,error,,\"day 10, line 3: expected a number, found 'x'\",700,1500,,\n\
+             10,2,error,,\"day 10, line 3: expected a number, found 'x'\",700,1500,,\n"
+        );
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        let json = json(&records()[1..4]);
+        assert_eq!(
+            json,
+            "[\n  \
+             {\"day\": 10, \"part\": 2, \"status\": \"ok\", \"answer\": \"##..\\n#\\\"#.\", \"parse_ns\": 700, \"elapsed_ns\": 1500},\n  \
+             {\"day\": 10, \"part\": 1, \"status\": \"not implemented\", \"parse_ns\": 700, \"elapsed_ns\": 1500},\n  \
+             {\"day\": 10, \"part\": 1, \"status\": \"error\", \"error\": \"day 10, line 3: expected a number, found 'x'\", \"parse_ns\": 700, \"elapsed_ns\": 1500}\n\
+             ]\n"
+        );
+    }
+
+    #[test]
+    fn json_includes_verdict() {
+        assert_eq!(
+            json(&records()[..1]),
+            "[\n  {\"day\": 10, \"part\": 1, \"status\": \"ok\", \"answer\": \"13140\", \
+             \"parse_ns\": 700, \"elapsed_ns\": 1500, \"verdict\": \"FAIL\", \"expected\": \"13141\"}\n]\n"
+        );
+        assert_eq!(summary(&records()), "PASS 0, FAIL 1, UNKNOWN 0");
+    }
+
+    #[test]
+    fn errors_are_reported_once() {
+        assert_eq!(
+            errors(&records()),
+            ["day 10, line 3: expected a number, found 'x'"]
+        );
+    }
+}
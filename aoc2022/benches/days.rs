@@ -0,0 +1,152 @@
+//! Benchmarks parsing and both parts of every day in the registry.
+//!
+//! ```text
+//! cargo bench --bench days -- [DAYS] [--timeout SECS] [--samples N]
+//!                             [--baseline NAME | --save-baseline NAME]
+//! ```
+//!
+//! `DAYS` selects days like `3,5,10-12`. Each benchmark collects `N`
+//! samples (default 2500) or runs until the timeout, which by default is
+//! long enough for the 100 samples `brunch` needs from a slow part.
+//!
+//! Results are compared to the previous run, or with `--baseline` to a
+//! named baseline that is left untouched. `--save-baseline` compares to and
+//! then replaces the named baseline.
+
+use aoc2022::Puzzle;
+use brunch::{Bench, Benches};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+
+const MIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct Options {
+    days: Vec<u8>,
+    timeout: Option<Duration>,
+    samples: Option<u32>,
+    baseline: Option<PathBuf>,
+    /// Whether `baseline` is a copy to remove when done.
+    copied: bool,
+}
+
+fn usage(message: &str) -> ! {
+    eprintln!(
+        "error: {message}\n\nUsage: cargo bench --bench days -- [DAYS] [--timeout SECS] \
+         [--samples N] [--baseline NAME | --save-baseline NAME]"
+    );
+    process::exit(2);
+}
+
+fn baseline(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("baselines");
+    fs::create_dir_all(&dir).unwrap_or_else(|err| usage(&format!("cannot create {dir:?}: {err}")));
+    dir.join(name)
+}
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| usage(&format!("{arg} needs a value")))
+        };
+        match arg.as_str() {
+            // Passed by `cargo bench` itself.
+            "--bench" => {}
+            "--timeout" => match value().parse() {
+                Ok(secs) => options.timeout = Some(Duration::from_secs(secs)),
+                Err(_) => usage("invalid timeout"),
+            },
+            "--samples" => match value().parse() {
+                Ok(samples) => options.samples = Some(samples),
+                Err(_) => usage("invalid number of samples"),
+            },
+            "--baseline" => {
+                // Compare with a copy, since brunch saves the run to the
+                // history it compared with.
+                let saved = baseline(&value());
+                let copy = env::temp_dir().join(format!("aoc2022-baseline-{}", process::id()));
+                if let Err(err) = fs::copy(&saved, &copy) {
+                    usage(&format!("cannot read baseline {saved:?}: {err}"));
+                }
+                options.baseline = Some(copy);
+                options.copied = true;
+            }
+            "--save-baseline" => {
+                options.baseline = Some(baseline(&value()));
+                options.copied = false;
+            }
+            flag if flag.starts_with('-') => usage(&format!("unknown option '{flag}'")),
+            days => options
+                .days
+                .extend(aoc2022::parse_days(days).unwrap_or_else(|err| usage(&err))),
+        }
+    }
+    options.days.sort_unstable();
+    options.days.dedup();
+    options
+}
+
+/// Times a single call, so that slow benchmarks get a long enough timeout.
+fn once<O>(f: impl FnOnce() -> O) -> (O, Duration) {
+    let now = Instant::now();
+    let output = f();
+    (output, now.elapsed())
+}
+
+fn bench(name: String, single: Duration, options: &Options) -> Bench {
+    let mut bench =
+        Bench::new(name).with_timeout(options.timeout.unwrap_or(MIN_TIMEOUT.max(single * 120)));
+    if let Some(samples) = options.samples {
+        bench = bench.with_samples(samples);
+    }
+    bench
+}
+
+fn bench_day(benches: &mut Benches, puzzle: Puzzle, options: &Options) {
+    let day = puzzle.day;
+    let filename = format!("../input/day{day}.txt");
+    let input = match fs::read_to_string(&filename) {
+        Ok(input) => input,
+        Err(err) => return eprintln!("skipping day {day}, cannot read {filename}: {err}"),
+    };
+    let parsed = match once(|| puzzle.parse(&input)) {
+        (Ok(parsed), single) => {
+            benches.push(
+                bench(format!("day{day}::parse"), single, options).run(|| puzzle.parse(&input)),
+            );
+            parsed
+        }
+        (Err(err), _) => return eprintln!("skipping day {day}: {err}"),
+    };
+    if let (Ok(Some(_)), single) = once(|| parsed.part1()) {
+        benches.push(bench(format!("day{day}::part1"), single, options).run(|| parsed.part1()));
+    }
+    if let (Ok(Some(_)), single) = once(|| parsed.part2()) {
+        benches.push(bench(format!("day{day}::part2"), single, options).run(|| parsed.part2()));
+    }
+    benches.push(Bench::spacer());
+}
+
+fn main() {
+    let options = parse_args();
+    if let Some(path) = &options.baseline {
+        env::set_var("BRUNCH_HISTORY", path);
+    }
+    let mut benches = Benches::default();
+    for puzzle in aoc2022::puzzles() {
+        if options.days.is_empty() || options.days.contains(&puzzle.day) {
+            bench_day(&mut benches, puzzle, &options);
+        }
+    }
+    benches.finish();
+    if let (Some(copy), true) = (&options.baseline, options.copied) {
+        _ = fs::remove_file(copy);
+    }
+}
@@ -0,0 +1,207 @@
+//! An indexable circular sequence for moving elements around, as when
+//! mixing the numbers of day 20.
+
+/// Elements in a circle, identified by their position when the circle was
+/// made so that equal values can be told apart.
+///
+/// The order is kept in blocks of about √n ids, so that finding, removing
+/// and inserting an element takes O(√n) rather than the O(n) of shifting a
+/// single vector.
+#[derive(Debug, Clone)]
+pub struct Circle<T> {
+    values: Vec<T>,
+    blocks: Vec<Vec<usize>>,
+    /// The block holding each id, or `usize::MAX` while it is removed.
+    block_of: Vec<usize>,
+    len: usize,
+    block_size: usize,
+    /// Insertions since the blocks were last evened out.
+    inserts: usize,
+}
+
+impl<T> Circle<T> {
+    /// A circle of `values` in order, with ids from 0.
+    pub fn new(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut circle = Circle {
+            values,
+            blocks: vec![(0..len).collect()],
+            block_of: vec![0; len],
+            len,
+            block_size: len.isqrt().max(16),
+            inserts: 0,
+        };
+        circle.rebalance();
+        circle
+    }
+
+    /// The number of elements in the circle.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The value of element `id`, whether or not it is in the circle.
+    pub fn value(&self, id: usize) -> &T {
+        &self.values[id]
+    }
+
+    /// The id of the element at `index`, counting from where the circle is
+    /// cut.
+    pub fn id_at(&self, index: usize) -> usize {
+        let (block, offset) = self.locate(index);
+        self.blocks[block][offset]
+    }
+
+    /// The value at `index`, counting around the circle as often as needed.
+    pub fn get(&self, index: usize) -> &T {
+        assert!(!self.is_empty(), "empty circle");
+        &self.values[self.id_at(index % self.len)]
+    }
+
+    /// The index of element `id`, or `None` if it has been removed.
+    pub fn position(&self, id: usize) -> Option<usize> {
+        let block = *self.block_of.get(id)?;
+        if block == usize::MAX {
+            return None;
+        }
+        let before: usize = self.blocks[..block].iter().map(Vec::len).sum();
+        let offset = self.blocks[block].iter().position(|&i| i == id)?;
+        Some(before + offset)
+    }
+
+    /// Removes the element at `index` and returns its id.
+    pub fn remove(&mut self, index: usize) -> usize {
+        let (block, offset) = self.locate(index);
+        let id = self.blocks[block].remove(offset);
+        self.block_of[id] = usize::MAX;
+        self.len -= 1;
+        id
+    }
+
+    /// Puts back the removed element `id` at `index`, before the element
+    /// there, or at the end for the length.
+    pub fn insert(&mut self, index: usize, id: usize) {
+        assert_eq!(self.block_of[id], usize::MAX, "{id} is in the circle");
+        assert!(index <= self.len, "index {index} out of bounds");
+        let (block, offset) = if index == self.len {
+            let last = self.blocks.len() - 1;
+            (last, self.blocks[last].len())
+        } else {
+            self.locate(index)
+        };
+        self.blocks[block].insert(offset, id);
+        self.block_of[id] = block;
+        self.len += 1;
+        self.inserts += 1;
+        if self.inserts >= self.block_size {
+            self.rebalance();
+        }
+    }
+
+    /// Moves element `id` `steps` places forward, or backward if negative,
+    /// past the other elements of the circle.
+    pub fn shift(&mut self, id: usize, steps: i64) {
+        let from = self.position(id).expect("the element is in the circle");
+        if self.len < 2 {
+            return;
+        }
+        self.remove(from);
+        let others = i64::try_from(self.len).unwrap();
+        let to = (i64::try_from(from).unwrap() + steps).rem_euclid(others);
+        self.insert(usize::try_from(to).unwrap(), id);
+    }
+
+    /// The values in order, from where the circle is cut.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.blocks.iter().flatten().map(|&id| &self.values[id])
+    }
+
+    /// The block and the offset in it of `index`.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let mut offset = index;
+        for (block, ids) in self.blocks.iter().enumerate() {
+            if offset < ids.len() {
+                return (block, offset);
+            }
+            offset -= ids.len();
+        }
+        panic!("index {index} out of bounds for a circle of {}", self.len);
+    }
+
+    /// Splits the order into blocks of the same size again.
+    fn rebalance(&mut self) {
+        let order: Vec<usize> = self.blocks.iter().flatten().copied().collect();
+        self.blocks = order
+            .chunks(self.block_size)
+            .map(<[usize]>::to_vec)
+            .collect();
+        if self.blocks.is_empty() {
+            self.blocks.push(Vec::new());
+        }
+        for (block, ids) in self.blocks.iter().enumerate() {
+            for &id in ids {
+                self.block_of[id] = block;
+            }
+        }
+        self.inserts = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Circle;
+
+    #[test]
+    fn remove_and_insert() {
+        let mut circle = Circle::new((0..100).collect());
+        assert_eq!(circle.remove(10), 10);
+        assert_eq!(circle.position(10), None);
+        assert_eq!(circle.position(11), Some(10));
+        circle.insert(0, 10);
+        assert_eq!(circle.position(10), Some(0));
+        assert_eq!(circle.position(0), Some(1));
+        assert_eq!(circle.remove(99), 99);
+        circle.insert(99, 99);
+        assert_eq!(*circle.get(99), 99);
+        assert_eq!(*circle.get(100), 10);
+    }
+
+    #[test]
+    fn shift() {
+        let mut circle = Circle::new(vec!['a', 'b', 'c', 'd']);
+        circle.shift(0, 1);
+        assert_eq!(circle.iter().collect::<String>(), "bacd");
+        circle.shift(0, -2);
+        assert_eq!(circle.iter().collect::<String>(), "bcad");
+        // Going around the other three elements leaves the same circle,
+        // cut in another place.
+        circle.shift(3, 3);
+        assert_eq!(circle.iter().collect::<String>(), "dbca");
+    }
+
+    #[test]
+    fn same_as_a_vector() {
+        // Shifts every element by a pseudo-random amount, comparing with
+        // removing and inserting in a plain vector.
+        let n = 1000;
+        let others = 999;
+        let mut circle = Circle::new((0..n).collect());
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut seed: i64 = 12345;
+        for id in (0..n).cycle().take(5 * n) {
+            seed = (seed * 1_103_515_245 + 12345) % (1 << 31);
+            let steps = seed % 4000 - 2000;
+            circle.shift(id, steps);
+            let from = order.iter().position(|&i| i == id).unwrap();
+            order.remove(from);
+            let to = (i64::try_from(from).unwrap() + steps).rem_euclid(others);
+            order.insert(usize::try_from(to).unwrap(), id);
+            assert_eq!(circle.position(id), Some(usize::try_from(to).unwrap()));
+        }
+        assert_eq!(circle.iter().copied().collect::<Vec<_>>(), order);
+    }
+}
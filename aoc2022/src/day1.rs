@@ -1,40 +1,61 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
 
-fn parse_file(filename: &str) -> Vec<Vec<i32>> {
-    std::fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .collect::<Vec<&str>>()
-        .split(|s| s.is_empty())
-        .map(|slice| {
-            slice
-                .iter()
-                .map(|string| string.parse::<i32>().unwrap())
-                .collect_vec()
-        })
-        .collect_vec()
+fn parse(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    let mut elves = vec![Vec::new()];
+    for line in lines(1, input) {
+        if line.text.is_empty() {
+            elves.push(Vec::new());
+        } else {
+            elves.last_mut().unwrap().push(line.number(line.text)?);
+        }
+    }
+    Ok(elves)
 }
 
-pub fn most_calories_from_file(filename: &str) -> i32 {
-    parse_file(filename)
-        .iter()
-        .map(|v| v.iter().sum::<i32>())
-        .max()
-        .unwrap()
+fn most_calories(elves: &[Vec<i32>]) -> i32 {
+    elves.iter().map(|v| v.iter().sum::<i32>()).max().unwrap()
 }
 
-pub fn three_most_calories_from_file(filename: &str) -> i32 {
-    parse_file(filename)
+fn three_most_calories(elves: &[Vec<i32>]) -> i32 {
+    elves
         .iter()
         .map(|v| v.iter().sum::<i32>())
         .sorted_by(|a, b| Ord::cmp(b, a)) // sort descending
-        .collect_vec()
-        .get(0..3)
-        .unwrap()
-        .iter()
+        .take(3)
         .sum()
 }
 
+pub fn most_calories_from_file(filename: &str) -> Result<i32, Error> {
+    Ok(most_calories(&Day1::parse_file(filename)?))
+}
+
+pub fn three_most_calories_from_file(filename: &str) -> Result<i32, Error> {
+    Ok(three_most_calories(&Day1::parse_file(filename)?))
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<Vec<i32>>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(elves: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(most_calories(elves)))
+    }
+
+    fn part2(elves: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(three_most_calories(elves)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::day1::{most_calories_from_file, three_most_calories_from_file};
@@ -43,11 +64,11 @@ mod tests {
     #[test_case("../testinput/day1.txt", 24000; "on test input")]
     #[test_case("../input/day1.txt", 69912; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(most_calories_from_file(filename), expected);
+        assert_eq!(most_calories_from_file(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day1.txt", 45000; "on test input")]
     #[test_case("../input/day1.txt", 208_180; "on real input")]
     fn part2(filename: &str, expected: i32) {
-        assert_eq!(three_most_calories_from_file(filename), expected);
+        assert_eq!(three_most_calories_from_file(filename).unwrap(), expected);
     }
 }
@@ -1,23 +1,29 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
-use std::{convert::TryFrom, fs::read_to_string};
+use std::convert::TryFrom;
 
-fn parse_file(filename: &str) -> Vec<i32> {
+fn parse(input: &str) -> Result<Vec<i32>, ParseError> {
     let mut timeline = Vec::<i32>::new();
     let mut x = 1;
-    for line in read_to_string(filename).unwrap().lines() {
-        if line.starts_with("addx") {
+    for line in lines(10, input) {
+        if line.text.starts_with("addx") {
             timeline.push(x);
             timeline.push(x);
-            x += line.get(5..).unwrap().parse::<i32>().unwrap();
-        } else {
+            let [value] = line.scan("addx {}")?;
+            x += line.number::<i32>(value)?;
+        } else if line.text == "noop" {
             timeline.push(x);
+        } else {
+            return Err(line.error(line.text, "expected 'noop' or 'addx'"));
         }
     }
-    timeline
+    if timeline.len() < 240 {
+        return Err(ParseError::input(10, "program shorter than 240 cycles"));
+    }
+    Ok(timeline)
 }
 
-pub fn signal_strength(filename: &str) -> i32 {
-    let timeline = parse_file(filename);
+fn strength(timeline: &[i32]) -> i32 {
     (0..6)
         .map(|base: usize| {
             let cycle = 20 + base * 40;
@@ -26,8 +32,8 @@ pub fn signal_strength(filename: &str) -> i32 {
         .sum()
 }
 
-pub fn crt(filename: &str) -> String {
-    parse_file(filename)
+fn render(timeline: &[i32]) -> String {
+    timeline
         .iter()
         .enumerate()
         .map(|(i, x)| {
@@ -43,6 +49,36 @@ pub fn crt(filename: &str) -> String {
         .join("\n")
 }
 
+pub fn signal_strength(filename: &str) -> Result<i32, Error> {
+    Ok(strength(&Day10::parse_file(filename)?))
+}
+
+pub fn crt(filename: &str) -> Result<String, Error> {
+    Ok(render(&Day10::parse_file(filename)?))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Vec<i32>;
+    type Answer1 = i32;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(timeline: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(strength(timeline)))
+    }
+
+    fn part2(timeline: &Self::Input) -> Result<Option<String>, String> {
+        Ok(Some(render(timeline)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{crt, signal_strength};
@@ -51,7 +87,7 @@ mod tests {
     #[test_case("../testinput/day10.txt", 13140; "on test input")]
     #[test_case("../input/day10.txt", 12980; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(signal_strength(filename), expected);
+        assert_eq!(signal_strength(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day10.txt",    
      "##..##..##..##..##..##..##..##..##..##..\n\
@@ -68,6 +104,6 @@ mod tests {
      #..#.#.#..#..#.#....#....#..#.#....#....\n\
      ###..#..#..##..####.#.....##..####.#...."; "on real input")]
     fn part2(filename: &str, expected: &str) {
-        assert_eq!(crt(filename), expected);
+        assert_eq!(crt(filename).unwrap(), expected);
     }
 }
@@ -1,5 +1,8 @@
+use crate::{
+    input::{lines, Line},
+    Error, ParseError, Solution,
+};
 use itertools::Itertools;
-use std::fs::read_to_string;
 
 #[derive(Debug)]
 enum Operation {
@@ -8,77 +11,77 @@ enum Operation {
     Square,
 }
 #[derive(Debug)]
-struct Monkey {
+pub struct Monkey {
     index: usize,
     items: Vec<u64>,
     operation: Operation,
     divisor: u64,
-    true_monkey: usize,
-    false_monkey: usize,
+    if_true: usize,
+    if_false: usize,
 }
 
-fn make_monkey(lines: &[&str]) -> Monkey {
-    let mut monkey = Monkey {
-        index: 0,
-        items: vec![],
-        operation: Operation::Add(0),
-        divisor: 1,
-        true_monkey: 0,
-        false_monkey: 0,
+fn make_monkey(index: usize, lines: &[Line]) -> Result<Monkey, ParseError> {
+    let [header, items, operation, test, if_true, if_false] = lines else {
+        return Err(lines[0].error(lines[0].text, "expected a monkey of six lines"));
     };
-    for line in lines {
-        if line.starts_with("Monkey") {
-            monkey.index = line.get(7..8).unwrap().parse().unwrap();
-        }
-        if line.starts_with("  S") {
-            for item in line
-                .get(18..)
-                .unwrap()
-                .split(", ")
-                .map(|s| s.parse::<u64>().unwrap())
-            {
-                monkey.items.push(item);
-            }
-        } else if line.starts_with("  O") {
-            let opstr = line.get(25..).unwrap();
-            if opstr == "old" {
-                monkey.operation = Operation::Square;
-            } else {
-                let operand = opstr.parse::<u64>().unwrap();
-                monkey.operation = match line.get(23..24).unwrap() {
-                    "*" => Operation::Mul(operand),
-                    "+" => Operation::Add(operand),
-                    _ => panic!("illegal operand {}", line.get(23..24).unwrap()),
-                };
-            }
-        } else if line.starts_with("  T") {
-            monkey.divisor = line.get(21..).unwrap().parse().unwrap();
-        } else if line.starts_with("    If true") {
-            monkey.true_monkey = line.get(29..).unwrap().parse().unwrap();
-        } else if line.starts_with("    If false") {
-            monkey.false_monkey = line.get(30..).unwrap().parse().unwrap();
-        }
+    let [no] = header.scan("Monkey {}:")?;
+    if header.number::<usize>(no)? != index {
+        return Err(header.error(no, format!("expected monkey {index}")));
     }
-    monkey
+    let (_, items) = items.trim().scan_prefix::<0>("Starting items:")?;
+    let [op, operand] = operation.trim().scan("Operation: new = old {} {}")?;
+    let [divisor] = test.trim().scan("Test: divisible by {}")?;
+    let [true_target] = if_true.trim().scan("If true: throw to monkey {}")?;
+    let [false_target] = if_false.trim().scan("If false: throw to monkey {}")?;
+    Ok(Monkey {
+        index,
+        items: items
+            .text
+            .split(", ")
+            .filter(|item| !item.is_empty())
+            .map(|item| items.number(item))
+            .collect::<Result<_, _>>()?,
+        operation: match (op, operand) {
+            ("*", "old") => Operation::Square,
+            ("+", "old") => Operation::Mul(2),
+            ("*", operand) => Operation::Mul(operation.number(operand)?),
+            ("+", operand) => Operation::Add(operation.number(operand)?),
+            (op, _) => return Err(operation.error(op, "expected '*' or '+'")),
+        },
+        divisor: test.number(divisor)?,
+        if_true: if_true.number(true_target)?,
+        if_false: if_false.number(false_target)?,
+    })
 }
 
-fn parse_file(filename: &str) -> Vec<Monkey> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .collect_vec()
-        .split(|s| s.is_empty())
-        .map(make_monkey)
+fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys = lines(11, input)
         .collect_vec()
+        .split(|line| line.text.is_empty())
+        .filter(|lines| !lines.is_empty())
+        .enumerate()
+        .map(|(index, lines)| make_monkey(index, lines))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(monkey) = monkeys
+        .iter()
+        .find(|m| m.if_true >= monkeys.len() || m.if_false >= monkeys.len())
+    {
+        return Err(ParseError::input(
+            11,
+            format!("monkey {} throws to a missing monkey", monkey.index),
+        ));
+    }
+    Ok(monkeys)
 }
 
-pub fn monkey_business(filename: &str, is_part1: bool) -> u64 {
-    let monkeys = parse_file(filename);
+/// Monkey business after 20 rounds with relief (part 1) or 10000 rounds
+/// without.
+pub fn business(monkeys: &[Monkey], is_part1: bool) -> u64 {
     let mut items = monkeys.iter().map(|m| m.items.clone()).collect_vec();
     let mut inspections = vec![0; monkeys.len()];
     let modulus: u64 = monkeys.iter().map(|m| m.divisor).product();
     for _ in 1..=if is_part1 { 20 } else { 10000 } {
-        for monkey in &monkeys {
+        for monkey in monkeys {
             for index in 0..items[monkey.index].len() {
                 let item = items[monkey.index][index];
                 let mut level = match monkey.operation {
@@ -90,11 +93,11 @@ pub fn monkey_business(filename: &str, is_part1: bool) -> u64 {
                     level /= 3;
                 } else {
                     level %= modulus;
-                };
+                }
                 if level % monkey.divisor == 0 {
-                    items[monkey.true_monkey].push(level);
+                    items[monkey.if_true].push(level);
                 } else {
-                    items[monkey.false_monkey].push(level);
+                    items[monkey.if_false].push(level);
                 }
                 inspections[monkey.index] += 1;
             }
@@ -104,6 +107,32 @@ pub fn monkey_business(filename: &str, is_part1: bool) -> u64 {
     inspections.iter().sorted().rev().take(2).product()
 }
 
+pub fn monkey_business(filename: &str, is_part1: bool) -> Result<u64, Error> {
+    Ok(business(&Day11::parse_file(filename)?, is_part1))
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input = Vec<Monkey>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(monkeys: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(business(monkeys, true)))
+    }
+
+    fn part2(monkeys: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(business(monkeys, false)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::monkey_business;
@@ -112,12 +141,12 @@ mod tests {
     #[test_case("../testinput/day11.txt", 10605; "on test input")]
     #[test_case("../input/day11.txt", 50616; "on real input")]
     fn part1(filename: &str, expected: u64) {
-        assert_eq!(monkey_business(filename, true), expected);
+        assert_eq!(monkey_business(filename, true).unwrap(), expected);
     }
 
     #[test_case("../testinput/day11.txt", 2_713_310_158; "on test input")]
     #[test_case("../input/day11.txt", 11_309_046_332; "on real input")]
     fn part2(filename: &str, expected: u64) {
-        assert_eq!(monkey_business(filename, false), expected);
+        assert_eq!(monkey_business(filename, false).unwrap(), expected);
     }
 }
@@ -1,8 +1,6 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fs::read_to_string,
-};
+use std::collections::{HashMap, HashSet};
 
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
@@ -11,37 +9,43 @@ struct Pos {
     y: usize,
 }
 
-struct Input {
+pub struct Input {
     start: Pos,
     end: Pos,
     map: Vec<Vec<i32>>,
 }
 
-fn parse_file(filename: &str) -> Input {
-    let mut start = Pos { x: 0, y: 0 };
-    let mut end = Pos { x: 0, y: 0 };
-    let map = read_to_string(filename)
-        .unwrap()
-        .lines()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut start = None;
+    let mut end = None;
+    let map = lines(12, input)
         .enumerate()
         .map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(x, c)| {
-                    if c == 'S' {
-                        start = Pos { x, y };
-                        'a' as i32
-                    } else if c == 'E' {
-                        end = Pos { x, y };
-                        'z' as i32
-                    } else {
-                        c as i32
+            line.text
+                .char_indices()
+                .map(|(x, c)| match c {
+                    'S' => {
+                        start = Some(Pos { x, y });
+                        Ok('a' as i32)
                     }
+                    'E' => {
+                        end = Some(Pos { x, y });
+                        Ok('z' as i32)
+                    }
+                    'a'..='z' => Ok(c as i32),
+                    _ => Err(line.error_at(x, "expected an elevation a-z, 'S' or 'E'")),
                 })
-                .collect_vec()
+                .collect()
         })
-        .collect_vec();
-    Input { start, end, map }
+        .collect::<Result<Vec<Vec<i32>>, _>>()?;
+    if map.is_empty() || map.iter().any(|row| row.len() != map[0].len()) {
+        return Err(ParseError::input(12, "expected a rectangular map"));
+    }
+    Ok(Input {
+        start: start.ok_or_else(|| ParseError::input(12, "missing start 'S'"))?,
+        end: end.ok_or_else(|| ParseError::input(12, "missing end 'E'"))?,
+        map,
+    })
 }
 
 fn h(a: Pos, b: Option<Pos>) -> i32 {
@@ -72,7 +76,7 @@ fn reconstruct_path(came_from: &HashMap<Pos, Pos>, start: &Pos) -> Vec<Pos> {
     path
 }
 
-fn neighbours(p: Pos, map: &Vec<Vec<i32>>) -> Vec<Pos> {
+fn neighbours(p: Pos, map: &[Vec<i32>]) -> Vec<Pos> {
     let mut n = Vec::<Pos>::new();
     if p.x > 0 && map[p.y][p.x] - map[p.y][p.x - 1] < 2 {
         n.push(Pos { x: p.x - 1, y: p.y });
@@ -89,7 +93,7 @@ fn neighbours(p: Pos, map: &Vec<Vec<i32>>) -> Vec<Pos> {
     n
 }
 
-fn a_star(start: Pos, end: Option<Pos>, map: &Vec<Vec<i32>>) -> usize {
+fn a_star(start: Pos, end: Option<Pos>, map: &[Vec<i32>]) -> Result<usize, String> {
     let mut open_set = HashSet::<Pos>::new();
     open_set.insert(start);
 
@@ -110,7 +114,7 @@ fn a_star(start: Pos, end: Option<Pos>, map: &Vec<Vec<i32>>) -> usize {
             .next()
             .unwrap();
         if is_goal(current, end) {
-            return reconstruct_path(&came_from, &current).len() - 1;
+            return Ok(reconstruct_path(&came_from, &current).len() - 1);
         }
         open_set.remove(&current);
         for neighbour in neighbours(current, map) {
@@ -126,32 +130,62 @@ fn a_star(start: Pos, end: Option<Pos>, map: &Vec<Vec<i32>>) -> usize {
             }
         }
     }
-    panic!("no path found");
+    Err("no path found".to_string())
+}
+
+pub fn shortest_path(filename: &str) -> Result<Result<usize, String>, Error> {
+    let input = Day12::parse_file(filename)?;
+    Ok(a_star(input.end, Some(input.start), &input.map))
 }
 
-pub fn shortest_path(filename: &str) -> usize {
-    let input = parse_file(filename);
-    a_star(input.end, Some(input.start), &input.map)
+pub fn shortest_path_from_any_a(filename: &str) -> Result<Result<usize, String>, Error> {
+    let input = Day12::parse_file(filename)?;
+    Ok(a_star(input.end, None, &input.map))
 }
 
-pub fn shortest_path_from_any_a(filename: &str) -> usize {
-    let input = parse_file(filename);
-    a_star(input.end, None, &input.map)
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Input = Input;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Option<usize>, String> {
+        a_star(input.end, Some(input.start), &input.map).map(Some)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Option<usize>, String> {
+        a_star(input.end, None, &input.map).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{shortest_path, shortest_path_from_any_a};
+    use super::{parse, shortest_path, shortest_path_from_any_a, Day12};
+    use crate::Solution;
     use test_case::test_case;
 
+    #[test]
+    fn no_path() {
+        let input = parse("SaE\n").unwrap();
+        assert_eq!(Day12::part1(&input), Err("no path found".to_string()));
+        assert_eq!(Day12::part2(&input), Err("no path found".to_string()));
+    }
+
     #[test_case("../testinput/day12.txt", 31; "on test input")]
     #[test_case("../input/day12.txt", 534; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(shortest_path(filename), expected);
+        assert_eq!(shortest_path(filename).unwrap(), Ok(expected));
     }
     #[test_case("../testinput/day12.txt", 29; "on test input")]
     #[test_case("../input/day12.txt", 525; "on real input")]
     fn part2(filename: &str, expected: usize) {
-        assert_eq!(shortest_path_from_any_a(filename), expected);
+        assert_eq!(shortest_path_from_any_a(filename).unwrap(), Ok(expected));
     }
 }
@@ -1,5 +1,9 @@
+use crate::{
+    input::{lines, Line},
+    Error, ParseError, Solution,
+};
 use itertools::Itertools;
-use std::{borrow::ToOwned, cmp::Ordering, fs::read_to_string, iter::zip};
+use std::{borrow::ToOwned, cmp::Ordering, iter::zip};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Item {
@@ -37,30 +41,26 @@ impl PartialOrd for Item {
     }
 }
 
-#[derive(Debug)]
-struct Pair {
-    left: Item,
-    right: Item,
-}
-
 #[derive(Debug)]
 enum StackItem {
     ListStart,
     ListItem(Item),
 }
 
-fn parse_item(line: &str) -> Item {
+fn parse_item(line: &Line) -> Result<Item, ParseError> {
     let mut stack = Vec::<StackItem>::new();
     let mut current_int: Option<u32> = None;
 
-    for c in line.chars() {
+    for (pos, c) in line.text.char_indices() {
         if c == '[' {
             stack.push(StackItem::ListStart);
-        } else if c.is_numeric() {
-            let d = c.to_digit(10).unwrap();
+        } else if let Some(d) = c.to_digit(10) {
             current_int = match current_int {
-                Some(i) => Some(i * 10 + d),
+                Some(i) => i.checked_mul(10).and_then(|i| i.checked_add(d)),
                 None => Some(d),
+            };
+            if current_int.is_none() {
+                return Err(line.error_at(pos, "number too large"));
             }
         } else if c == ',' {
             if let Some(i) = current_int {
@@ -73,10 +73,12 @@ fn parse_item(line: &str) -> Item {
                 current_int = None;
             }
 
-            let last_liststart = stack
+            let Some(last_liststart) = stack
                 .iter()
                 .rposition(|si| matches!(si, StackItem::ListStart))
-                .unwrap();
+            else {
+                return Err(line.error_at(pos, "unmatched ']'"));
+            };
             let list = stack
                 .drain(last_liststart..)
                 .skip(1) // skip ListStart
@@ -89,38 +91,46 @@ fn parse_item(line: &str) -> Item {
                 })
                 .collect_vec();
             stack.push(StackItem::ListItem(Item::List(list)));
+        } else {
+            return Err(line.error_at(pos, "unexpected character"));
         }
     }
-    match stack.first().unwrap() {
-        StackItem::ListItem(item) => item.clone(),
-        StackItem::ListStart => panic!("Parse error"),
+    match stack.as_slice() {
+        [StackItem::ListItem(item)] => Ok(item.clone()),
+        _ => Err(line.error_at_end("expected a single complete list")),
     }
 }
 
-pub fn sum_indices(filename: &str) -> usize {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .collect_vec()
-        .split(|s| s.is_empty())
-        .map(|split| Pair {
-            left: parse_item(split[0]),
-            right: parse_item(split[1]),
-        })
+fn parse(input: &str) -> Result<Vec<Item>, ParseError> {
+    let packets = lines(13, input)
+        .filter(|line| !line.text.is_empty())
+        .map(|line| parse_item(&line))
+        .collect::<Result<Vec<_>, _>>()?;
+    if packets.len() % 2 == 1 {
+        return Err(ParseError::input(13, "expected pairs of packets"));
+    }
+    Ok(packets)
+}
+
+fn divider(n: u32) -> Item {
+    Item::List(vec![Item::List(vec![Item::Int(n)])])
+}
+
+fn ordered_pairs(packets: &[Item]) -> usize {
+    packets
+        .chunks(2)
         .enumerate()
-        .filter(|(_, p)| p.left.cmp(&p.right) == Ordering::Less)
+        .filter(|(_, pair)| pair[0].cmp(&pair[1]) == Ordering::Less)
         .map(|(i, _)| i + 1)
         .sum()
 }
 
-pub fn decoder_key(filename: &str) -> usize {
-    let dividers = vec![parse_item("[[2]]"), parse_item("[[6]]")];
-    let items = read_to_string(filename)
-        .unwrap()
-        .lines()
-        .filter(|s| !s.is_empty())
-        .map(parse_item)
-        .chain(dividers.iter().map(ToOwned::to_owned))
+fn divider_positions(packets: &[Item]) -> usize {
+    let dividers = [divider(2), divider(6)];
+    let items = packets
+        .iter()
+        .chain(dividers.iter())
+        .map(ToOwned::to_owned)
         .sorted()
         .collect_vec();
 
@@ -130,19 +140,55 @@ pub fn decoder_key(filename: &str) -> usize {
         .product()
 }
 
+pub fn sum_indices(filename: &str) -> Result<usize, Error> {
+    Ok(ordered_pairs(&Day13::parse_file(filename)?))
+}
+
+pub fn decoder_key(filename: &str) -> Result<usize, Error> {
+    Ok(divider_positions(&Day13::parse_file(filename)?))
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Input = Vec<Item>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(packets: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(ordered_pairs(packets)))
+    }
+
+    fn part2(packets: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(divider_positions(packets)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{decoder_key, sum_indices};
+    use super::{decoder_key, parse, sum_indices};
     use test_case::test_case;
 
+    #[test_case("[\u{663}]\n[1]\n", "day 13, line 1, column 2: unexpected character, found '\u{663}'"; "non-ascii digit")]
+    #[test_case("[99999999999]\n[1]\n", "day 13, line 1, column 11: number too large, found '9'"; "overflow")]
+    fn invalid_number(input: &str, expected: &str) {
+        assert_eq!(parse(input).unwrap_err().to_string(), expected);
+    }
+
     #[test_case("../testinput/day13.txt", 13; "on test input")]
     #[test_case("../input/day13.txt", 5252; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(sum_indices(filename), expected);
+        assert_eq!(sum_indices(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day13.txt", 140; "on test input")]
     #[test_case("../input/day13.txt", 20592; "on real input")]
     fn part2(filename: &str, expected: usize) {
-        assert_eq!(decoder_key(filename), expected);
+        assert_eq!(decoder_key(filename).unwrap(), expected);
     }
 }
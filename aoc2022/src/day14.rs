@@ -1,7 +1,5 @@
-use std::{
-    cmp::{max, min},
-    fs::read_to_string,
-};
+use crate::{input::lines, Error, ParseError, Solution};
+use std::cmp::{max, min};
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 struct Pos {
@@ -10,7 +8,7 @@ struct Pos {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-enum Block {
+pub enum Block {
     Air,
     Rock,
     Sand,
@@ -23,37 +21,46 @@ struct Line {
 
 type Map = Vec<Vec<Block>>;
 
-fn parse_file(filename: &str) -> Map {
-    let mut lines = Vec::<Line>::new();
-    read_to_string(filename).unwrap().lines().for_each(|line| {
-        let splits = line.split(" -> ");
+const WIDTH: usize = 1000;
+
+fn parse(input: &str) -> Result<Map, ParseError> {
+    let mut rock_lines = Vec::<Line>::new();
+    for line in lines(14, input) {
         let mut last: Option<Pos> = None;
-        for split in splits {
-            let mut coords = split.split(',');
+        for split in line.text.split(" -> ") {
+            let [x, y] = line.split(split, ",")?;
             let pos = Pos {
-                x: coords.next().unwrap().parse::<usize>().unwrap(),
-                y: coords.next().unwrap().parse::<usize>().unwrap(),
+                x: line.number(x)?,
+                y: line.number(y)?,
             };
+            if !(1..WIDTH - 1).contains(&pos.x) {
+                return Err(line.error(x, "x coordinate out of range"));
+            }
             if let Some(ref lastpos) = last {
-                lines.push(Line {
+                if lastpos.x != pos.x && lastpos.y != pos.y {
+                    return Err(line.error(split, "expected a horizontal or vertical line"));
+                }
+                rock_lines.push(Line {
                     start: lastpos.clone(),
                     end: pos.clone(),
                 });
             }
             last = Some(pos);
         }
-    });
-    let max_y = lines
+    }
+    let Some(max_y) = rock_lines
         .iter()
         .map(|line| max(line.start.y, line.end.y))
         .max()
-        .unwrap();
+    else {
+        return Err(ParseError::input(14, "no rock paths"));
+    };
     let mut map = Vec::<Vec<Block>>::new();
     for _ in 0..=max_y + 1 {
-        map.push(vec![Block::Air; 1000]);
+        map.push(vec![Block::Air; WIDTH]);
     }
 
-    for line in lines {
+    for line in rock_lines {
         if line.start.x == line.end.x {
             for row in map
                 .get_mut(min(line.start.y, line.end.y)..=max(line.start.y, line.end.y))
@@ -62,12 +69,15 @@ fn parse_file(filename: &str) -> Map {
                 row[line.start.x] = Block::Rock;
             }
         } else if line.start.y == line.end.y {
-            for x in min(line.start.x, line.end.x)..=max(line.start.x, line.end.x) {
-                map[line.start.y][x] = Block::Rock;
+            for block in map[line.start.y]
+                .get_mut(min(line.start.x, line.end.x)..=max(line.start.x, line.end.x))
+                .unwrap()
+            {
+                *block = Block::Rock;
             }
         }
     }
-    map
+    Ok(map)
 }
 
 fn block_at(pos: &Pos, map: &Map, floor: Option<usize>) -> Block {
@@ -81,7 +91,7 @@ fn block_at(pos: &Pos, map: &Map, floor: Option<usize>) -> Block {
 
 fn lowest_rock(map: &Map) -> usize {
     map.iter()
-        .rposition(|row| row.iter().any(|b| *b == Block::Rock))
+        .rposition(|row| row.contains(&Block::Rock))
         .unwrap()
 }
 
@@ -107,8 +117,7 @@ fn sand_pos(map: &Map, floor: Option<usize>) -> Option<Pos> {
     None
 }
 
-pub fn sand_before_abyss(filename: &str) -> i32 {
-    let mut map = parse_file(filename);
+fn fill_until_abyss(mut map: Map) -> i32 {
     let mut i = 0;
     loop {
         if let Some(pos) = sand_pos(&map, None) {
@@ -120,8 +129,7 @@ pub fn sand_before_abyss(filename: &str) -> i32 {
     }
 }
 
-pub fn sand_to_rest(filename: &str) -> i32 {
-    let mut map = parse_file(filename);
+fn fill_until_blocked(mut map: Map) -> i32 {
     let floor = lowest_rock(&map) + 2;
     let mut i = 0;
     loop {
@@ -137,6 +145,36 @@ pub fn sand_to_rest(filename: &str) -> i32 {
     }
 }
 
+pub fn sand_before_abyss(filename: &str) -> Result<i32, Error> {
+    Ok(fill_until_abyss(Day14::parse_file(filename)?))
+}
+
+pub fn sand_to_rest(filename: &str) -> Result<i32, Error> {
+    Ok(fill_until_blocked(Day14::parse_file(filename)?))
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+
+    type Input = Map;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(map: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(fill_until_abyss(map.clone())))
+    }
+
+    fn part2(map: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(fill_until_blocked(map.clone())))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{sand_before_abyss, sand_to_rest};
@@ -145,11 +183,11 @@ mod tests {
     #[test_case("../testinput/day14.txt", 24; "on test input")]
     #[test_case("../input/day14.txt", 638; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(sand_before_abyss(filename), expected);
+        assert_eq!(sand_before_abyss(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day14.txt", 93; "on test input")]
     #[test_case("../input/day14.txt", 31722; "on real input")]
     fn part2(filename: &str, expected: i32) {
-        assert_eq!(sand_to_rest(filename), expected);
+        assert_eq!(sand_to_rest(filename).unwrap(), expected);
     }
 }
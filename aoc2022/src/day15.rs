@@ -1,13 +1,15 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use fxhash::FxHashSet as HashSet;
 use itertools::Itertools;
-use std::fs::read_to_string;
 
+#[derive(Debug)]
 struct Pos {
     x: i64,
     y: i64,
 }
 
-struct Report {
+#[derive(Debug)]
+pub struct Report {
     sensor: Pos,
     beacon: Pos,
     range: i64,
@@ -19,42 +21,31 @@ struct Range {
     last: i64,
 }
 
-fn get_num(s: &str) -> i64 {
-    let mut sign: i64 = 1;
-    s.chars().fold(0, |acc, c| {
-        if c == '-' {
-            sign = -1;
-        }
-        if c.is_numeric() {
-            10 * acc + i64::try_from(c.to_digit(10).unwrap()).unwrap()
-        } else {
-            acc
-        }
-    }) * sign
-}
-
-fn parse_file(filename: &str) -> Vec<Report> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+fn parse(input: &str) -> Result<Vec<Report>, ParseError> {
+    let reports: Vec<Report> = lines(15, input)
         .map(|line| {
-            let split = line.split(' ').collect_vec();
+            let [sx, sy, bx, by] =
+                line.scan("Sensor at x={}, y={}: closest beacon is at x={}, y={}")?;
             let sensor = Pos {
-                x: get_num(split[2]),
-                y: get_num(split[3]),
+                x: line.number(sx)?,
+                y: line.number(sy)?,
             };
             let beacon = Pos {
-                x: get_num(split[8]),
-                y: get_num(split[9]),
+                x: line.number(bx)?,
+                y: line.number(by)?,
             };
             let range = (sensor.y - beacon.y).abs() + (sensor.x - beacon.x).abs();
-            Report {
+            Ok(Report {
                 sensor,
                 beacon,
                 range,
-            }
+            })
         })
-        .collect_vec()
+        .collect::<Result<_, _>>()?;
+    if reports.is_empty() {
+        return Err(ParseError::input(15, "no sensors"));
+    }
+    Ok(reports)
 }
 
 fn overlap_or_adjacent(a: &Range, b: &Range) -> bool {
@@ -89,11 +80,11 @@ fn row_in_range(row_no: i64, report: &Report) -> bool {
         < (report.sensor.y - report.beacon.y).abs() + (report.sensor.x - report.beacon.x).abs()
 }
 
-pub fn cannot_contain(filename: &str, row_no: i64) -> i64 {
-    let reports = parse_file(filename);
+/// Number of positions in row `row_no` where a beacon cannot be.
+pub fn excluded_positions(reports: &[Report], row_no: i64) -> i64 {
     let mut ranges = Vec::<Range>::new();
     let mut occupied: HashSet<i64> = HashSet::default();
-    for report in &reports {
+    for report in reports {
         if row_in_range(row_no, report) {
             let dx = report.range - (report.sensor.y - row_no).abs();
             ranges.push(Range {
@@ -121,11 +112,11 @@ pub fn cannot_contain(filename: &str, row_no: i64) -> i64 {
     not_present
 }
 
-pub fn tuning_frequency(filename: &str, area: i64) -> i64 {
-    let reports = parse_file(filename);
+/// Tuning frequency of the only possible beacon position in `0..=area`.
+pub fn find_beacon(reports: &[Report], area: i64) -> Result<i64, String> {
     for row_no in 0..=area {
         let mut ranges = Vec::<Range>::new();
-        for report in &reports {
+        for report in reports {
             if row_in_range(row_no, report) {
                 let dx = report.range - (report.sensor.y - row_no).abs();
                 ranges.push(Range {
@@ -137,29 +128,74 @@ pub fn tuning_frequency(filename: &str, area: i64) -> i64 {
 
         for range in merge_ranges(&ranges) {
             if range.first > 0 && range.first <= area {
-                return 4_000_000 * (range.first - 1) + row_no;
+                return Ok(4_000_000 * (range.first - 1) + row_no);
             }
             if range.last >= 0 && range.last < area {
-                return 4_000_000 * (range.last + 1) + row_no;
+                return Ok(4_000_000 * (range.last + 1) + row_no);
             }
         }
     }
-    panic!("no valid solution found");
+    Err("no position left for the beacon".to_string())
+}
+
+pub fn cannot_contain(filename: &str, row_no: i64) -> Result<i64, Error> {
+    Ok(excluded_positions(&Day15::parse_file(filename)?, row_no))
+}
+
+pub fn tuning_frequency(filename: &str, area: i64) -> Result<Result<i64, String>, Error> {
+    Ok(find_beacon(&Day15::parse_file(filename)?, area))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    type Input = Vec<Report>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(reports: &Self::Input) -> Result<Option<i64>, String> {
+        Ok(Some(excluded_positions(reports, 2_000_000)))
+    }
+
+    fn part2(reports: &Self::Input) -> Result<Option<i64>, String> {
+        find_beacon(reports, 4_000_000).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{cannot_contain, tuning_frequency};
+    use super::{cannot_contain, find_beacon, parse, tuning_frequency};
     use test_case::test_case;
 
+    #[test]
+    fn no_sensors() {
+        assert_eq!(parse("").unwrap_err().to_string(), "day 15: no sensors");
+    }
+
+    #[test]
+    fn no_beacon() {
+        // The sensor sees every position of the area.
+        let reports = parse("Sensor at x=2, y=2: closest beacon is at x=2, y=6\n").unwrap();
+        assert_eq!(
+            find_beacon(&reports, 4),
+            Err("no position left for the beacon".to_string())
+        );
+    }
+
     #[test_case("../testinput/day15.txt", 10, 26; "on test input")]
     #[test_case("../input/day15.txt", 2_000_000, 4_985_193; "on real input")]
     fn part1(filename: &str, row_no: i64, expected: i64) {
-        assert_eq!(cannot_contain(filename, row_no), expected);
+        assert_eq!(cannot_contain(filename, row_no).unwrap(), expected);
     }
     #[test_case("../testinput/day15.txt", 20, 56_000_011; "on test input")]
     #[test_case("../input/day15.txt", 4_000_000, 11_583_882_601_918; "on real input")]
     fn part2(filename: &str, area: i64, expected: i64) {
-        assert_eq!(tuning_frequency(filename, area), expected);
+        assert_eq!(tuning_frequency(filename, area).unwrap(), Ok(expected));
     }
 }
@@ -1,554 +1,693 @@
 #![allow(clippy::too_many_lines)]
 
-use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use crate::{input::lines, Error, ParseError, Solution};
+use fxhash::FxHashMap as HashMap;
 use itertools::Itertools;
-use std::{cmp::min, collections::VecDeque, fs::read_to_string};
+use std::{
+    collections::{hash_map::Entry, VecDeque},
+    fmt,
+};
 
 #[derive(Debug, Clone)]
-struct Edge {
-    cost: u32,
-    dest: String,
-}
-
-#[derive(Debug, Clone)]
-struct Valve {
+pub struct Valve {
     flow: u32,
-    tunnels: Vec<Edge>,
-    open: bool,
+    tunnels: Vec<String>,
 }
 
-#[derive(Debug)]
-struct QueueItem {
-    valve: String,
-    minutes: u32,
-    pressure: u32,
-    increase: u32,
-    came_from: Vec<String>,
-    valves: Map,
-}
+type Map = HashMap<String, Valve>;
 
-#[derive(Debug)]
-struct DualQueueItem {
-    pressure: u32,
-    increase: u32,
-    me: Visitor,
-    elephant: Visitor,
-    valves: Map,
-    visited: HashSet<String>,
-    opened: HashSet<String>,
-}
-#[derive(Debug, Clone)]
-struct Visitor {
-    minutes: u32,
-    valve: String,
-    came_from: Vec<String>,
+/// The valves as given, while [`Day16::parse`] compresses them to a [`Graph`].
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let mut map: Map = HashMap::default();
+    let mut references = Vec::new();
+    for line in lines(16, input) {
+        let ([name, flow], rest) = line.scan_prefix("Valve {} has flow rate={};")?;
+        let flow = line.number(flow)?;
+        let list = match rest.scan_prefix::<0>("tunnels lead to valves") {
+            Ok((_, list)) => list,
+            Err(_) => rest.scan_prefix::<0>("tunnel leads to valve")?.1,
+        };
+        let tunnels = list
+            .text
+            .split(", ")
+            .map(|dest| {
+                references.push((line, dest));
+                dest.to_string()
+            })
+            .collect_vec();
+        map.insert(name.to_string(), Valve { flow, tunnels });
+    }
+    if let Some((line, dest)) = references.iter().find(|(_, dest)| !map.contains_key(*dest)) {
+        return Err(line.error(dest, "no such valve"));
+    }
+    if !map.contains_key("AA") {
+        return Err(ParseError::input(16, "no valve AA to start from"));
+    }
+    Ok(map)
 }
 
+/// The valves with flow and the start valve, with the shortest distance
+/// between each pair of them. Valves are identified by their index into
+/// `names`, with the valves with flow first so that they double as bits.
 #[derive(Debug, Clone)]
-struct Move {
-    visitor: Visitor,
-    increase: u32,
-    opened: Option<String>,
+pub struct Graph {
+    pub names: Vec<String>,
+    pub flows: Vec<u32>,
+    pub dist: Vec<Vec<u32>>,
+    /// The number of valves with flow.
+    pub targets: usize,
 }
 
-type Map = HashMap<String, Valve>;
-
-fn get_num(s: &str) -> u32 {
-    s.chars().fold(0, |acc, c| -> u32 {
-        if c.is_numeric() {
-            10 * acc + c.to_digit(10).unwrap()
-        } else {
-            acc
+const UNREACHABLE: u32 = u32::MAX / 2;
+
+/// The most valves with flow that [`ValveProblem::solve`] takes on, as its
+/// tables have an entry for every subset of them.
+pub const MAX_TARGETS: usize = 20;
+
+/// The distance to every valve that can be reached from `from`, and the
+/// valve before it on the way there.
+fn bfs<'a>(valves: &'a Map, from: &'a str) -> HashMap<&'a str, (u32, &'a str)> {
+    let mut reached = HashMap::default();
+    reached.insert(from, (0, from));
+    let mut queue = VecDeque::from([from]);
+    while let Some(valve) = queue.pop_front() {
+        let dist = reached[valve].0;
+        for dest in &valves[valve].tunnels {
+            if let Entry::Vacant(entry) = reached.entry(dest.as_str()) {
+                entry.insert((dist + 1, valve));
+                queue.push_back(dest);
+            }
         }
-    })
+    }
+    reached
 }
 
-fn parse_file(filename: &str) -> Map {
-    let mut map: Map = HashMap::default();
-    // println!("digraph G {{");
-    for line in read_to_string(filename).unwrap().lines() {
-        let split = line.split(' ').collect_vec();
-        let name = split[1].to_string();
-        let flow = get_num(split[4]);
-        // println!("  {} [label=\"{}: {}\"]", name, name, flow);
-        let tunnels = split
-            .get(9..)
-            .unwrap()
+impl Graph {
+    /// Compresses the valves to the ones with flow and `start`, or `None` if
+    /// there is no valve `start`.
+    pub fn new(valves: &Map, start: &str) -> Option<Self> {
+        let mut names = valves
             .iter()
-            .map(|t| {
-                if t.contains(',') {
-                    Edge {
-                        cost: 1,
-                        dest: t.get(..t.len() - 1).unwrap().to_string(),
-                    }
-                } else {
-                    Edge {
-                        cost: 1,
-                        dest: (*t).to_string(),
-                    }
-                }
-            })
+            .filter(|(_, valve)| valve.flow > 0)
+            .map(|(name, _)| name.clone())
+            .sorted()
             .collect_vec();
-        // for t in &tunnels {
-        //     println!("  {} -> {}", name, t);
-        // }
-        map.insert(
-            name.to_string(),
-            Valve {
-                flow,
-                tunnels,
-                open: false,
-            },
-        );
+        let targets = names.len();
+        if valves.get(start)?.flow == 0 {
+            names.push(start.to_string());
+        }
+        let dist = names
+            .iter()
+            .map(|from| {
+                let reached = bfs(valves, from);
+                names
+                    .iter()
+                    .map(|to| {
+                        reached
+                            .get(to.as_str())
+                            .map_or(UNREACHABLE, |&(dist, _)| dist)
+                    })
+                    .collect()
+            })
+            .collect();
+        Some(Graph {
+            flows: names.iter().map(|name| valves[name].flow).collect(),
+            names,
+            dist,
+            targets,
+        })
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|valve| valve == name)
     }
-    // println!("}}");
-    map
 }
 
-fn path(valves: &Map) -> u32 {
-    let mut queue = VecDeque::<QueueItem>::new();
-
-    queue.push_back(QueueItem {
-        valve: "AA".to_string(),
-        minutes: 0,
-        pressure: 0,
-        increase: 0,
-        came_from: Vec::<String>::new(),
-        valves: valves.clone(),
-    });
-
-    let max_increase: u32 = valves.values().map(|valve| valve.flow).sum();
-    let mut max_pressure: u32 = 0;
-
-    while !queue.is_empty() {
-        // println!("queue.len() = {}", queue.len());
-        let item = queue.pop_front().unwrap();
-        if item.minutes > 30 {
-            continue;
-        }
-        // println!(
-        //     "minute {}: looking at QueueItem {} pressure: {}, increase: {}, {:?}",
-        //     item.minutes, item.valve, item.pressure, item.increase, item.came_from
-        // );
-
-        if item.minutes >= 7 && item.increase < 20 || item.minutes >= 13 && item.increase < 50 {
-            // println!("  increase too low");
-            continue;
-        }
+/// A valve opened by one agent during the given minute, counting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub minute: u32,
+    pub valve: String,
+}
 
-        if item.increase == max_increase {
-            let final_pressure = item.pressure + (30 - item.minutes) * item.increase;
-            // println!(
-            //     "final_pressure = {} (max_pressure = {})",
-            //     final_pressure, max_pressure
-            // );
-            if final_pressure > max_pressure {
-                max_pressure = final_pressure;
-            }
-            continue;
-        }
+/// The most pressure that can be released, and the valves each agent opens
+/// to release it in the order they are opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub pressure: u32,
+    pub agents: Vec<Vec<Opening>>,
+}
 
-        if item.pressure > max_pressure {
-            max_pressure = item.pressure;
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Move(String),
+    Open(String),
+}
 
-        if item.minutes < 30 {
-            let valve = item.valves.get(&item.valve).unwrap();
-
-            let mut path = item.came_from.clone();
-            path.push(item.valve.clone());
-
-            for tunnel in &valve.tunnels {
-                // if let Some(last) = item.came_from.iter().last() {
-                //     println!("dest = {}, came_from.last = {}", dest, last);
-                // } else {
-                //     println!("no last");
-                // }
-                if item.came_from.is_empty() || tunnel.dest != *item.came_from.last().unwrap() {
-                    queue.push_back(QueueItem {
-                        valve: tunnel.dest.to_string(),
-                        minutes: item.minutes + tunnel.cost,
-                        pressure: item.pressure + tunnel.cost * item.increase,
-                        increase: item.increase,
-                        came_from: path.clone(),
-                        valves: item.valves.clone(),
-                    });
-                }
-            }
-            if item.minutes < 29 && !valve.open && valve.flow > 0 {
-                // println!("opening valve {}", item.valve);
-                let mut mutvalves = item.valves.clone();
-                mutvalves
-                    .entry(item.valve.clone())
-                    .and_modify(|v| v.open = true);
-                for tunnel in &valve.tunnels {
-                    // println!("opening valve {} ({}). item.pressure = {}, tunnel.cost = {}, next pressure = {} ",
-                    //     item.valve, valve.flow, item.pressure, tunnel.cost, item.pressure + (tunnel.cost + 1) * item.increase + valve.flow);
-                    queue.push_back(QueueItem {
-                        valve: tunnel.dest.to_string(),
-                        minutes: item.minutes + tunnel.cost + 1,
-                        pressure: item.pressure
-                            + item.increase
-                            + tunnel.cost * (item.increase + valve.flow),
-                        increase: item.increase + valve.flow,
-                        came_from: path.clone(),
-                        valves: mutvalves.clone(),
-                    });
-                }
-            }
-        }
-        // println!("queue is now {:?}", queue);
-    }
-    max_pressure
+/// What one agent does during a minute, counting from 1, and the pressure
+/// released in total by the end of that minute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub minute: u32,
+    pub agent: usize,
+    pub action: Action,
+    pub released: u32,
 }
 
-fn dual_path(valves: &Map) -> u32 {
-    let mut queue = VecDeque::<DualQueueItem>::new();
-
-    queue.push_back(DualQueueItem {
-        pressure: 0,
-        increase: 0,
-        valves: valves.clone(),
-        me: Visitor {
-            minutes: 1,
-            valve: "AA".to_string(),
-            came_from: Vec::<String>::new(),
-        },
-        elephant: Visitor {
-            minutes: 1,
-            valve: "AA".to_string(),
-            came_from: Vec::<String>::new(),
-        },
-        visited: HashSet::default(),
-        opened: HashSet::default(),
-    });
-
-    let max_increase: u32 = valves.values().map(|valve| valve.flow).sum();
-    let mut max_pressure: u32 = 0;
-
-    while !queue.is_empty() {
-        // println!("queue.len() = {}", queue.len());
-        let item = queue.pop_front().unwrap();
-
-        let minutes = min(item.elephant.minutes, item.me.minutes);
-
-        if minutes > 26 {
-            continue;
-        }
-        println!(
-            "Minute {}: looking at DualQueueItem pressure: {}, increase: {}, me: {} {} {:?}, elephant: {} {} {:?}, visited {:?}, opened {:?}",
-            minutes, item.pressure, item.increase, item.me.minutes, item.me.valve,
-            item.me.came_from, item.elephant.minutes, item.elephant.valve, item.elephant.came_from, item.visited, item.opened);
+/// A schedule spelled out tunnel by tunnel, in the order of the minutes and
+/// then the agents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub minutes: u32,
+    pub start: String,
+    pub agents: usize,
+    pub pressure: u32,
+    pub steps: Vec<Step>,
+    /// The flow of each opened valve.
+    pub flows: Vec<(String, u32)>,
+}
 
-        let open: HashSet<String> = item
-            .valves
-            .iter()
-            .filter(|(_, valve)| valve.open)
-            .map(|(name, _)| name.clone())
-            .collect();
+/// Agents that all start at the same valve and open valves together until
+/// time runs out. Every valve is opened at most once, so the agents split
+/// the valves with flow between them.
+#[derive(Debug, Clone)]
+pub struct ValveProblem {
+    pub minutes: u32,
+    pub start: String,
+    pub agents: usize,
+}
 
-        if item.visited.contains(&item.me.valve)
-            && item.visited.contains(&item.elephant.valve)
-            && open.is_subset(&item.opened)
-        {
-            println!("already been here");
-            continue;
+/// The most pressure a single agent can release by opening exactly the
+/// valves of each bitmask, and the order to open them in.
+struct Routes<'a> {
+    graph: &'a Graph,
+    best: Vec<u32>,
+    orders: Vec<Vec<usize>>,
+}
+
+impl Routes<'_> {
+    fn search(
+        &mut self,
+        valve: usize,
+        minutes: u32,
+        opened: usize,
+        pressure: u32,
+        route: &mut Vec<usize>,
+    ) {
+        if pressure > self.best[opened] {
+            self.best[opened] = pressure;
+            self.orders[opened].clone_from(route);
         }
-        // if ((item.me.minutes >= 7 || item.elephant.minutes >= 7) && item.increase < 20)
-        //     || (item.me.minutes >= 13 || item.elephant.minutes >= 13) && item.increase < 50
-        // {
-        //     println!("  increase too low");
-        //     continue;
-        // }
-
-        if item.increase == max_increase {
-            let final_pressure = item.pressure + (26 - minutes) * item.increase;
-            println!(
-                "final_pressure = {} (max_pressure = {})",
-                final_pressure, max_pressure
-            );
-            if final_pressure > max_pressure {
-                println!(
-                    "max_pressure updated from {} to {}",
-                    max_pressure, final_pressure
+        for target in 0..self.graph.targets {
+            let cost = self.graph.dist[valve][target] + 1;
+            if opened & 1 << target == 0 && cost < minutes {
+                let left = minutes - cost;
+                route.push(target);
+                self.search(
+                    target,
+                    left,
+                    opened | 1 << target,
+                    pressure + left * self.graph.flows[target],
+                    route,
                 );
-                max_pressure = final_pressure;
+                route.pop();
             }
-            continue;
         }
+    }
+}
 
-        if item.pressure > max_pressure {
-            println!(
-                "max_pressure updated from {} to {}",
-                max_pressure, item.pressure
-            );
-            max_pressure = item.pressure;
+impl ValveProblem {
+    pub fn new(minutes: u32, start: &str, agents: usize) -> Self {
+        ValveProblem {
+            minutes,
+            start: start.to_string(),
+            agents,
         }
+    }
 
-        if minutes < 26 {
-            let mut my_moves = Vec::<Move>::new();
-            if item.me.minutes == minutes {
-                let my_valve = item.valves.get(&item.me.valve).unwrap();
-                let mut my_path = item.me.came_from.clone();
-                my_path.push(item.me.valve.clone());
-                for tunnel in &my_valve.tunnels {
-                    if item.me.came_from.is_empty()
-                        || tunnel.dest != *item.me.came_from.last().unwrap()
-                    {
-                        my_moves.push(Move {
-                            visitor: Visitor {
-                                minutes: minutes + tunnel.cost,
-                                valve: tunnel.dest.to_string(),
-                                came_from: my_path.clone(),
-                            },
-                            increase: 0,
-                            opened: None,
-                        });
-                    }
-                }
-                if minutes < 25 && !my_valve.open && my_valve.flow > 0 {
-                    // println!("opening valve {}", item.valve);
-                    for tunnel in &my_valve.tunnels {
-                        // println!("opening valve {} ({}). item.pressure = {}, tunnel.cost = {}, next pressure = {} ",
-                        //     item.valve, valve.flow, item.pressure, tunnel.cost, item.pressure + (tunnel.cost + 1) * item.increase + valve.flow);
-                        my_moves.push(Move {
-                            visitor: Visitor {
-                                minutes: minutes + 1 + tunnel.cost,
-                                valve: tunnel.dest.to_string(),
-                                came_from: my_path.clone(),
-                            },
-                            increase: my_valve.flow,
-                            opened: Some(item.me.valve.clone()),
-                        });
-                    }
+    /// Finds the best schedule, or `None` if the graph does not keep the
+    /// start valve or has more than [`MAX_TARGETS`] valves with flow. Work
+    /// and memory grow with two to the number of valves with flow, and with
+    /// three to it for more than two agents.
+    pub fn solve(&self, graph: &Graph) -> Option<Schedule> {
+        if graph.targets > MAX_TARGETS {
+            return None;
+        }
+        let start = graph.id(&self.start)?;
+        let all = (1 << graph.targets) - 1;
+        let mut routes = Routes {
+            graph,
+            best: vec![0; all + 1],
+            orders: vec![Vec::new(); all + 1],
+        };
+        routes.search(start, self.minutes, 0, 0, &mut Vec::new());
+
+        // The best single route among each bitmask and all of its subsets.
+        let mut within = routes.best.iter().copied().zip(0..=all).collect_vec();
+        for bit in 0..graph.targets {
+            for opened in 0..=all {
+                if opened & 1 << bit != 0 && within[opened ^ 1 << bit].0 > within[opened].0 {
+                    within[opened] = within[opened ^ 1 << bit];
                 }
-            } else {
-                my_moves.push(Move {
-                    visitor: item.me.clone(),
-                    increase: 0,
-                    opened: None,
-                });
             }
-            // println!("my_moves = {:?}", my_moves);
-
-            let mut elephant_moves = Vec::<Move>::new();
-            if item.elephant.minutes == minutes {
-                let elephant_valve = item.valves.get(&item.elephant.valve).unwrap();
-                let mut elephant_path = item.elephant.came_from.clone();
-                elephant_path.push(item.elephant.valve.clone());
-                for tunnel in &elephant_valve.tunnels {
-                    if item.elephant.came_from.is_empty()
-                        || tunnel.dest != *item.elephant.came_from.last().unwrap()
-                    {
-                        elephant_moves.push(Move {
-                            visitor: Visitor {
-                                minutes: minutes + tunnel.cost,
-                                valve: tunnel.dest.to_string(),
-                                came_from: elephant_path.clone(),
-                            },
-                            increase: 0,
-                            opened: None,
-                        });
+        }
+        // Adds one agent at a time, each taking the valves of one route and
+        // leaving the rest to the agents before it. Only the last agent has
+        // to consider every valve.
+        let mut agents = vec![within];
+        for agent in 1..self.agents {
+            let previous = &agents[agent - 1];
+            let masks = if agent + 1 == self.agents {
+                all..=all
+            } else {
+                0..=all
+            };
+            let mut next = vec![(0, 0); all + 1];
+            for mask in masks {
+                let mut taken = mask;
+                loop {
+                    let pressure = routes.best[taken] + previous[mask ^ taken].0;
+                    if pressure > next[mask].0 {
+                        next[mask] = (pressure, taken);
                     }
-                }
-                if minutes < 25 && !elephant_valve.open && elephant_valve.flow > 0 {
-                    // println!("opening valve {}", item.valve);
-                    for tunnel in &elephant_valve.tunnels {
-                        // println!("opening valve {} ({}). item.pressure = {}, tunnel.cost = {}, next pressure = {} ",
-                        //     item.valve, valve.flow, item.pressure, tunnel.cost, item.pressure + (tunnel.cost + 1) * item.increase + valve.flow);
-                        elephant_moves.push(Move {
-                            visitor: Visitor {
-                                minutes: minutes + 1 + tunnel.cost,
-                                valve: tunnel.dest.to_string(),
-                                came_from: elephant_path.clone(),
-                            },
-                            increase: elephant_valve.flow,
-                            opened: Some(item.elephant.valve.clone()),
-                        });
+                    if taken == 0 {
+                        break;
                     }
+                    taken = (taken - 1) & mask;
                 }
-            } else {
-                elephant_moves.push(Move {
-                    visitor: item.elephant.clone(),
-                    increase: 0,
-                    opened: None,
-                });
             }
-            // println!("elephant_moves = {:?}", elephant_moves);
-
-            for my_move in my_moves {
-                for elephant_move in &elephant_moves {
-                    if my_move.opened.is_some()
-                        && elephant_move.opened.is_some()
-                        && my_move.opened == elephant_move.opened
-                    {
-                        continue;
-                    }
-                    let mut opened = item.opened.clone();
-                    let mut mutvalves = item.valves.clone();
-                    if let Some(opened_valve) = my_move.opened.clone() {
-                        opened.insert(opened_valve.clone());
-                        mutvalves.entry(opened_valve).and_modify(|v| v.open = true);
-                    }
-                    if let Some(opened_valve) = elephant_move.opened.clone() {
-                        opened.insert(opened_valve.clone());
-                        mutvalves.entry(opened_valve).and_modify(|v| v.open = true);
+            agents.push(next);
+        }
+
+        let mut schedule = Schedule {
+            pressure: 0,
+            agents: Vec::new(),
+        };
+        let mut mask = all;
+        for agent in (0..self.agents).rev() {
+            let (pressure, taken) = agents[agent][mask];
+            if agent + 1 == self.agents {
+                schedule.pressure = pressure;
+            }
+            let (mut valve, mut minutes) = (start, self.minutes);
+            let openings = routes.orders[taken]
+                .iter()
+                .map(|&target| {
+                    minutes -= graph.dist[valve][target] + 1;
+                    valve = target;
+                    Opening {
+                        minute: self.minutes - minutes,
+                        valve: graph.names[target].clone(),
                     }
-                    let next_minute = min(my_move.visitor.minutes, elephant_move.visitor.minutes);
-                    let time = next_minute - minutes;
-                    let increase = item.increase + my_move.increase + elephant_move.increase;
-                    let mut visited = item.visited.clone();
-                    visited.insert(item.me.valve.clone());
-                    visited.insert(item.elephant.valve.clone());
-                    // println!(
-                    //     "Pushing for minute {}: pressure {}, increase {}, me {:?}, elephant {:?}, opened ({:?}, {:?}), visited: {:?}",
-                    //     next_minute,
-                    //     item.pressure + time * increase,
-                    //     increase,
-                    //     my_move.visitor,
-                    //     elephant_move.visitor,
-                    //     my_move.opened,
-                    //     elephant_move.opened,
-                    //     visited,
-                    // );
-                    queue.push_back(DualQueueItem {
-                        pressure: item.pressure + time * increase,
-                        increase,
-                        valves: mutvalves,
-                        me: my_move.visitor.clone(),
-                        elephant: elephant_move.visitor.clone(),
-                        visited,
-                        opened,
-                    });
+                })
+                .collect();
+            schedule.agents.push(openings);
+            mask ^= taken;
+        }
+        schedule.agents.reverse();
+        Some(schedule)
+    }
+
+    /// Finds the best schedule like [`ValveProblem::solve`] on the
+    /// uncompressed valves, and spells it out as moves and openings.
+    pub fn plan(&self, valves: &Map) -> Option<Plan> {
+        let schedule = self.solve(&Graph::new(valves, &self.start)?)?;
+        let mut openings = Vec::new();
+        let mut steps = Vec::new();
+        for (agent, schedule) in schedule.agents.iter().enumerate() {
+            let (mut valve, mut minute) = (self.start.as_str(), 0);
+            for opening in schedule {
+                let reached = bfs(valves, valve);
+                let (mut route, mut at) = (Vec::new(), opening.valve.as_str());
+                while at != valve {
+                    route.push(at);
+                    at = reached[at].1;
+                }
+                for hop in route.into_iter().rev() {
+                    minute += 1;
+                    steps.push((minute, agent, Action::Move(hop.to_string())));
                 }
+                valve = &opening.valve;
+                minute += 1;
+                steps.push((minute, agent, Action::Open(opening.valve.clone())));
+                openings.push((minute, valves[&opening.valve].flow));
             }
         }
+        steps.sort_by_key(|&(minute, agent, _)| (minute, agent));
+        let released = |minute: u32| -> u32 {
+            openings
+                .iter()
+                .map(|&(opened, flow)| flow * minute.saturating_sub(opened))
+                .sum()
+        };
+        Some(Plan {
+            minutes: self.minutes,
+            start: self.start.clone(),
+            agents: self.agents,
+            pressure: schedule.pressure,
+            steps: steps
+                .into_iter()
+                .map(|(minute, agent, action)| Step {
+                    minute,
+                    agent,
+                    action,
+                    released: released(minute),
+                })
+                .collect(),
+            flows: schedule
+                .agents
+                .iter()
+                .flatten()
+                .map(|opening| (opening.valve.clone(), valves[&opening.valve].flow))
+                .collect(),
+        })
     }
+}
 
-    //         let elephant_valve = item.valves.get(&item.elephant.valve).unwrap();
-    //         let mut elephant_path = item.elephant.came_from.clone();
-    //         elephant_path.push(item.elephant.valve.clone());
-
-    //         for tunnel in &my_valve.tunnels {
-    //             // if let Some(last) = item.came_from.iter().last() {
-    //             //     println!("dest = {}, came_from.last = {}", dest, last);
-    //             // } else {
-    //             //     println!("no last");
-    //             // }
-    //             if item.came_from.is_empty() || tunnel.dest != *item.came_from.last().unwrap() {
-    //                 queue.push_back(QueueItem {
-    //                     valve: tunnel.dest.to_string(),
-    //                     minutes: item.minutes + tunnel.cost,
-    //                     pressure: item.pressure + tunnel.cost * item.increase,
-    //                     increase: item.increase,
-    //                     came_from: path.clone(),
-    //                     valves: item.valves.clone(),
-    //                 });
-    //             }
-    //         }
-
-    //     }
-    //     // println!("queue is now {:?}", queue);
-    // }
-    max_pressure
+fn agent_does(agent: usize, action: &Action) -> String {
+    let (subject, s) = match agent {
+        0 => ("You".to_string(), ""),
+        1 => ("The elephant".to_string(), "s"),
+        _ => (format!("Elephant {agent}"), "s"),
+    };
+    match action {
+        Action::Move(valve) => format!("{subject} move{s} to valve {valve}."),
+        Action::Open(valve) => format!("{subject} open{s} valve {valve}."),
+    }
 }
 
-#[allow(dead_code)]
-fn print_graph(graph: &Map) {
-    println!("digraph G {{");
-    for (node, valve) in graph {
-        println!("  {} [label=\"{}: {}\"]", node, node, valve.flow);
-        for tunnel in &valve.tunnels {
-            println!("  {} -> {} [label=\"{}\"]", node, tunnel.dest, tunnel.cost);
+fn valves_open(open: &[&(String, u32)]) -> String {
+    let releasing: u32 = open.iter().map(|(_, flow)| flow).sum();
+    match open {
+        [] => "No valves are open.".to_string(),
+        [(valve, _)] => format!("Valve {valve} is open, releasing {releasing} pressure."),
+        [(first, _), (second, _)] => {
+            format!("Valves {first} and {second} are open, releasing {releasing} pressure.")
         }
+        [init @ .., (last, _)] => format!(
+            "Valves {}, and {last} are open, releasing {releasing} pressure.",
+            init.iter().map(|(valve, _)| valve).join(", ")
+        ),
     }
-    println!("}}");
 }
 
-fn simplify_graph(graph: &Map) -> Map {
-    // print_graph(graph);
-    let mut simple: Map = graph.clone();
-    for node in graph.keys() {
-        if let Some(valve) = simple.get(node) {
-            let mut mutsimple = simple.clone();
-            // println!("Looking at {} {:?}", node, valve);
-            if node != "AA" && valve.flow == 0 {
-                // println!("removing");
-                for tunnel in &(valve.tunnels) {
-                    // println!(" looking at tunnel to {} cost {}", tunnel.dest, tunnel.cost);
-                    let node_other = valve
-                        .tunnels
-                        .iter()
-                        .filter(|vt| *vt.dest != tunnel.dest)
-                        .cloned()
-                        .collect_vec();
-                    // println!("  tunnels to other than {} = {:?}", tunnel.dest, node_other);
-
-                    if simple.contains_key(&tunnel.dest) {
-                        let mut dest_other = simple
-                            .get(&tunnel.dest)
-                            .unwrap()
-                            .tunnels
-                            .iter()
-                            .filter(|e| *e.dest != *node)
-                            .cloned()
-                            .collect_vec();
-
-                        dest_other.extend(node_other.iter().map(|vt| Edge {
-                            dest: vt.dest.clone(),
-                            cost: tunnel.cost + vt.cost,
-                        }));
-                        // println!("  tunnels from {} now {:?}", tunnel.dest, dest_other);
-                        let valve = simple.get(&tunnel.dest).unwrap();
-                        let new_valve = Valve {
-                            tunnels: dest_other.clone(),
-                            ..*valve
-                        };
-                        mutsimple.insert(tunnel.dest.clone(), new_valve);
-
-                        // println!(
-                        //     "   check: simple[{}] = {:?}",
-                        //     tunnel.dest,
-                        //     mutsimple.get(&tunnel.dest).unwrap()
-                        // );
-                    }
+/// The minute by minute account of the puzzle, with the agents after the
+/// first called the elephant, elephant 2 and so on.
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut open: Vec<&(String, u32)> = Vec::new();
+        let mut steps = self.steps.iter().peekable();
+        for minute in 1..=self.minutes {
+            if minute > 1 {
+                writeln!(f)?;
+            }
+            open.sort();
+            writeln!(f, "== Minute {minute} ==\n{}", valves_open(&open))?;
+            while let Some(step) = steps.next_if(|step| step.minute == minute) {
+                writeln!(f, "{}", agent_does(step.agent, &step.action))?;
+                if let Action::Open(valve) = &step.action {
+                    open.extend(self.flows.iter().find(|(name, _)| name == valve));
                 }
-                mutsimple.remove(node);
-                simple = mutsimple;
             }
+        }
+        Ok(())
+    }
+}
+
+const COLORS: [&str; 4] = ["red", "blue", "darkgreen", "orange"];
+
+/// The tunnels walked and the valves opened by each agent of a plan. With
+/// `legs`, the tunnels are between the valves opened one after the other, as
+/// in the compressed graph.
+#[derive(Default)]
+struct Highlights<'a> {
+    tunnels: HashMap<(&'a str, &'a str), usize>,
+    opened: HashMap<&'a str, (usize, u32)>,
+}
+
+impl<'a> Highlights<'a> {
+    fn new(plan: Option<&'a Plan>, legs: bool) -> Self {
+        let mut highlights = Highlights::default();
+        let Some(plan) = plan else {
+            return highlights;
+        };
+        let mut at = vec![plan.start.as_str(); plan.agents];
+        for step in &plan.steps {
+            let valve = match (&step.action, legs) {
+                (Action::Move(valve), false) | (Action::Open(valve), true) => valve.as_str(),
+                (Action::Open(valve), false) => {
+                    highlights.opened.insert(valve, (step.agent, step.minute));
+                    continue;
+                }
+                (Action::Move(_), true) => continue,
+            };
+            let from = at[step.agent];
+            if from != valve {
+                let tunnel = (from.min(valve), from.max(valve));
+                highlights.tunnels.entry(tunnel).or_insert(step.agent);
+            }
+            if legs {
+                highlights.opened.insert(valve, (step.agent, step.minute));
+            }
+            at[step.agent] = valve;
+        }
+        highlights
+    }
+
+    fn node(&self, name: &str, flow: u32) -> String {
+        match self.opened.get(name) {
+            Some(&(agent, minute)) => format!(
+                "  {name} [label=\"{name}: {flow}\\nopened in minute {minute}\", style=filled, \
+                 color={}, fontcolor=white];\n",
+                COLORS[agent % COLORS.len()]
+            ),
+            None => format!("  {name} [label=\"{name}: {flow}\"];\n"),
+        }
+    }
+
+    fn tunnel(&self, from: &str, to: &str, label: Option<u32>) -> String {
+        let mut attributes = label
+            .map(|dist| format!("label={dist}"))
+            .into_iter()
+            .collect_vec();
+        if let Some(&agent) = self.tunnels.get(&(from.min(to), from.max(to))) {
+            attributes.push(format!(
+                "color={}, penwidth=3",
+                COLORS[agent % COLORS.len()]
+            ));
+        }
+        if attributes.is_empty() {
+            format!("  {from} -- {to};\n")
         } else {
-            println!("node {} already gone", node);
+            format!("  {from} -- {to} [{}];\n", attributes.join(", "))
+        }
+    }
+}
+
+/// Graphviz DOT for the tunnels between the valves, each labelled with its
+/// flow. The tunnels walked and valves opened in `plan` are highlighted with
+/// a color for each agent.
+pub fn dot(valves: &Map, plan: Option<&Plan>) -> String {
+    let highlights = Highlights::new(plan, false);
+    let mut out = String::from("graph valves {\n");
+    for name in valves.keys().sorted() {
+        out.push_str(&highlights.node(name, valves[name].flow));
+    }
+    for name in valves.keys().sorted() {
+        for dest in &valves[name].tunnels {
+            // Tunnels lead both ways, but make sure that one way tunnels are
+            // not lost.
+            if name < dest || !valves[dest].tunnels.contains(name) {
+                out.push_str(&highlights.tunnel(name, dest, None));
+            }
         }
     }
-    // print_graph(&simple);
-    simple
+    out.push_str("}\n");
+    out
 }
 
-pub fn max_pressure(filename: &str) -> u32 {
-    let valves = parse_file(filename);
-    let simple = simplify_graph(&valves);
-    path(&simple)
+impl Graph {
+    /// Graphviz DOT like [`dot`], with the distance on each tunnel and the
+    /// tunnels taken from one opened valve to the next highlighted.
+    pub fn dot(&self, plan: Option<&Plan>) -> String {
+        let highlights = Highlights::new(plan, true);
+        let mut out = String::from("graph valves {\n");
+        for (name, &flow) in self.names.iter().zip(&self.flows) {
+            out.push_str(&highlights.node(name, flow));
+        }
+        for (from, to) in (0..self.names.len()).tuple_combinations() {
+            if self.dist[from][to] != UNREACHABLE {
+                let (from_name, to_name) = (&self.names[from], &self.names[to]);
+                out.push_str(&highlights.tunnel(from_name, to_name, Some(self.dist[from][to])));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
 }
 
-pub fn max_pressure_with_elephant(filename: &str) -> u32 {
-    let valves = parse_file(filename);
-    let simple = simplify_graph(&valves);
-    dual_path(&simple)
+/// Parsing makes sure that the graph keeps valve AA to start from.
+fn pressure(graph: &Graph, minutes: u32, agents: usize) -> u32 {
+    ValveProblem::new(minutes, "AA", agents)
+        .solve(graph)
+        .unwrap()
+        .pressure
+}
+
+pub fn max_pressure(filename: &str) -> Result<u32, Error> {
+    Ok(pressure(&Day16::parse_file(filename)?, 30, 1))
+}
+
+pub fn max_pressure_with_elephant(filename: &str) -> Result<u32, Error> {
+    Ok(pressure(&Day16::parse_file(filename)?, 26, 2))
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+
+    type Input = Graph;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    /// The valves compressed to the ones with flow and AA.
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let graph = Graph::new(&parse(input)?, "AA").unwrap();
+        if graph.targets > MAX_TARGETS {
+            return Err(ParseError::input(
+                16,
+                format!("more than {MAX_TARGETS} valves with flow"),
+            ));
+        }
+        Ok(graph)
+    }
+
+    fn part1(graph: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(pressure(graph, 30, 1)))
+    }
+
+    fn part2(graph: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(pressure(graph, 26, 2)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{max_pressure, max_pressure_with_elephant};
+    use super::{
+        dot, max_pressure, max_pressure_with_elephant, parse, Action, Day16, Graph, Opening,
+        ValveProblem, MAX_TARGETS,
+    };
+    use crate::Solution;
+    use std::fmt::Write;
     use test_case::test_case;
 
     #[test_case("../testinput/day16.txt", 1651; "on test input")]
     #[test_case("../input/day16.txt", 1873; "on real input")]
     fn part1(filename: &str, expected: u32) {
-        assert_eq!(max_pressure(filename), expected);
+        assert_eq!(max_pressure(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day16.txt", 1707; "on test input")]
-    #[test_case("../input/day16.txt", 1873; "on real input")]
+    #[test_case("../input/day16.txt", 2425; "on real input")]
     fn part2(filename: &str, expected: u32) {
-        assert_eq!(max_pressure_with_elephant(filename), expected);
+        assert_eq!(max_pressure_with_elephant(filename).unwrap(), expected);
+    }
+
+    #[test]
+    fn graph() {
+        let graph = Day16::parse_file("../testinput/day16.txt").unwrap();
+        assert_eq!(graph.names, ["BB", "CC", "DD", "EE", "HH", "JJ", "AA"]);
+        assert_eq!(graph.flows, [13, 2, 20, 3, 22, 21, 0]);
+        assert_eq!(graph.targets, 6);
+        let aa = graph.id("AA").unwrap();
+        assert_eq!(graph.dist[aa], [1, 2, 1, 2, 5, 2, 0]);
+        assert_eq!(graph.id("FF"), None);
+    }
+
+    #[test]
+    fn schedule() {
+        let valves = Day16::parse_file("../testinput/day16.txt").unwrap();
+        let opened = |agent: &[Opening]| {
+            agent
+                .iter()
+                .map(|opening| format!("{}@{}", opening.valve, opening.minute))
+                .collect::<Vec<_>>()
+        };
+        let schedule = ValveProblem::new(26, "AA", 2).solve(&valves).unwrap();
+        assert_eq!(schedule.pressure, 1707);
+        assert_eq!(opened(&schedule.agents[0]), ["DD@2", "HH@7", "EE@11"]);
+        assert_eq!(opened(&schedule.agents[1]), ["JJ@3", "BB@7", "CC@9"]);
+        let schedule = ValveProblem::new(26, "AA", 3).solve(&valves).unwrap();
+        assert_eq!(schedule.pressure, 1794);
+        assert_eq!(schedule.agents.len(), 3);
+        assert_eq!(ValveProblem::new(30, "ZZ", 1).solve(&valves), None);
+    }
+
+    #[test]
+    fn too_many_valves() {
+        // One valve with flow too many, each in a tunnel off AA.
+        let names: Vec<String> = (0..=MAX_TARGETS)
+            .map(|i| format!("B{}", char::from(b'A' + u8::try_from(i).unwrap())))
+            .collect();
+        let mut input = format!(
+            "Valve AA has flow rate=0; tunnels lead to valves {}\n",
+            names.join(", ")
+        );
+        for name in &names {
+            _ = writeln!(
+                input,
+                "Valve {name} has flow rate=1; tunnel leads to valve AA"
+            );
+        }
+        let graph = Graph::new(&parse(&input).unwrap(), "AA").unwrap();
+        assert_eq!(graph.targets, MAX_TARGETS + 1);
+        assert_eq!(ValveProblem::new(30, "AA", 1).solve(&graph), None);
+        assert_eq!(
+            Day16::parse(&input).unwrap_err().to_string(),
+            "day 16: more than 20 valves with flow"
+        );
+    }
+
+    #[test]
+    fn plan() {
+        let input = std::fs::read_to_string("../testinput/day16.txt").unwrap();
+        let plan = ValveProblem::new(30, "AA", 1)
+            .plan(&parse(&input).unwrap())
+            .unwrap();
+        assert_eq!(plan.pressure, 1651);
+        let last = plan.steps.last().unwrap();
+        assert_eq!(
+            (last.minute, &last.action),
+            (24, &Action::Open("CC".into()))
+        );
+        assert_eq!(last.released, 1651 - 6 * 81);
+        let narrative = plan.to_string();
+        assert!(narrative.starts_with(
+            "== Minute 1 ==\nNo valves are open.\nYou move to valve DD.\n\n\
+             == Minute 2 ==\nNo valves are open.\nYou open valve DD.\n\n\
+             == Minute 3 ==\nValve DD is open, releasing 20 pressure.\n"
+        ));
+        assert!(narrative.ends_with(
+            "== Minute 30 ==\nValves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.\n"
+        ));
+    }
+    #[test]
+    fn graphviz() {
+        let input = std::fs::read_to_string("../testinput/day16.txt").unwrap();
+        let valves = parse(&input).unwrap();
+        let bare = dot(&valves, None);
+        assert!(bare.starts_with("graph valves {\n  AA [label=\"AA: 0\"];\n"));
+        assert!(bare.contains("  AA -- DD;\n"));
+        assert_eq!(bare.matches(" -- ").count(), 10);
+
+        let plan = ValveProblem::new(30, "AA", 1).plan(&valves).unwrap();
+        let route = dot(&valves, Some(&plan));
+        assert!(route.contains("  AA -- DD [color=red, penwidth=3];\n"));
+        assert!(route.contains(
+            "  DD [label=\"DD: 20\\nopened in minute 2\", style=filled, color=red, fontcolor=white];\n"
+        ));
+        let compressed = Day16::parse(&input).unwrap().dot(Some(&plan));
+        assert!(compressed.contains("  EE -- JJ [label=4];\n"));
+        assert!(compressed.contains("  BB -- JJ [label=3, color=red, penwidth=3];\n"));
     }
 }
@@ -1,158 +1,576 @@
+use crate::{
+    input::{lines, Line},
+    Error, ParseError, Solution,
+};
+use fxhash::FxHashMap as HashMap;
 use itertools::Itertools;
-use std::fs::read_to_string;
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
 
-type Object = Vec<u8>;
+/// Rows of rock from the bottom up, with bit `x` set for rock `x` columns
+/// from the left wall.
+type Object = Vec<u64>;
 
-struct Rock {
+/// The rocks of the puzzle, in the order they fall.
+pub const SHAPES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##
+";
+
+pub struct Rock {
     sprite: Object,
     width: usize,
 }
 
-fn make_shapes() -> Vec<Rock> {
-    vec![
-        Rock {
-            sprite: vec![15],
-            width: 4,
-        },
-        Rock {
-            sprite: vec![2, 7, 2],
-            width: 3,
-        },
-        Rock {
-            sprite: vec![7, 1, 1],
-            width: 3,
-        },
-        Rock {
-            sprite: vec![1, 1, 1, 1],
-            width: 1,
-        },
-        Rock {
-            sprite: vec![3, 3],
-            width: 2,
-        },
-    ]
-}
-
-fn can_move_left(rock: &Rock, x: usize, y: usize, chamber: &Object) -> bool {
-    if x == 0 {
-        return false;
-    }
-    for i in 0..rock.sprite.len() {
-        if y + i < chamber.len() && chamber[y + i] & rock.sprite[i] << (7 - x - rock.width + 1) > 0
-        {
-            return false;
+/// Parses one rock drawn with `#` and `.`, top row first, and moves it
+/// against the left wall and the floor.
+fn parse_rock(rows: &[Line]) -> Result<Rock, ParseError> {
+    let mut sprite = Object::new();
+    for row in rows.iter().rev() {
+        if let Some(i) = row.text.find(|c| c != '#' && c != '.') {
+            return Err(row.error_at(i, "expected '#' or '.'"));
+        }
+        if row.text.len() > 64 {
+            return Err(row.error_at(64, "rock wider than 64"));
         }
+        sprite.push(
+            row.text
+                .match_indices('#')
+                .fold(0, |bits, (x, _)| bits | 1 << x),
+        );
     }
-    true
+    let Some(left) = sprite.iter().map(|row| row.trailing_zeros()).min() else {
+        return Err(rows[0].error_at(0, "empty rock"));
+    };
+    if left == 64 {
+        return Err(rows[0].error_at(0, "empty rock"));
+    }
+    // Blank rows inside the rock are part of its shape.
+    let bottom = sprite.iter().position(|&row| row != 0).unwrap();
+    sprite.drain(..bottom);
+    while sprite.last() == Some(&0) {
+        sprite.pop();
+    }
+    for row in &mut sprite {
+        *row >>= left;
+    }
+    let width = sprite
+        .iter()
+        .map(|row| 64 - row.leading_zeros() as usize)
+        .max()
+        .unwrap();
+    Ok(Rock { sprite, width })
+}
+
+/// A chamber of `width` columns where rocks of the given shapes fall in turn,
+/// each appearing two columns from the left wall and three rows above the
+/// tower.
+pub struct Tower {
+    shapes: Vec<Rock>,
+    width: usize,
 }
 
-fn can_move_right(rock: &Rock, x: usize, y: usize, chamber: &Object) -> bool {
-    if x + rock.width > 6 {
-        return false;
+impl Default for Tower {
+    /// The rocks and the chamber of the puzzle.
+    fn default() -> Self {
+        Tower::new(SHAPES, 7).unwrap()
     }
-    for i in 0..rock.sprite.len() {
-        if y + i < chamber.len()
-            && (chamber[y + i] & (rock.sprite[i] << (7 - x - rock.width - 1)) > 0)
-        {
-            // println!("false");
-            return false;
+}
+
+impl Tower {
+    /// Parses the shapes like [`SHAPES`], separated by blank lines, for a
+    /// chamber of up to 64 columns that has room for each of them.
+    pub fn new(shapes: &str, width: usize) -> Result<Self, ParseError> {
+        if !(1..=64).contains(&width) {
+            return Err(ParseError::input(17, "chamber width must be 1 to 64"));
+        }
+        let rows = lines(17, shapes).collect_vec();
+        let shapes = rows
+            .split(|row| row.text.trim().is_empty())
+            .filter(|rows| !rows.is_empty())
+            .map(parse_rock)
+            .collect::<Result<Vec<_>, _>>()?;
+        if shapes.is_empty() {
+            return Err(ParseError::input(17, "no rocks"));
         }
+        if shapes.iter().any(|rock| 2 + rock.width > width) {
+            return Err(ParseError::input(17, "rock too wide for the chamber"));
+        }
+        Ok(Tower { shapes, width })
     }
-    true
-}
 
-fn can_move_down(rock: &Rock, x: usize, y: usize, chamber: &Object) -> bool {
-    if y == 0 {
-        return false;
+    /// The height of the tower after `count` rocks. Once a rock falls with
+    /// the same shape, gust and surface as an earlier rock, the rocks in
+    /// between repeat until there are too few rocks left to do so again.
+    pub fn height(&self, jets: &Jets, count: usize) -> usize {
+        let mut chamber = Chamber {
+            width: self.width,
+            ..Chamber::default()
+        };
+        let mut seen = HashMap::default();
+        let mut skipped = 0;
+        let mut i = 0;
+        while i < count {
+            if skipped == 0 {
+                let state = (i % self.shapes.len(), chamber.gust, chamber.surface());
+                if let Some((before, height)) = seen.insert(state, (i, chamber.height)) {
+                    let cycles = (count - i) / (i - before);
+                    skipped = cycles * (chamber.height - height);
+                    i += cycles * (i - before);
+                    if i == count {
+                        break;
+                    }
+                }
+            }
+            let shape = i % self.shapes.len();
+            chamber.drop(shape, &self.shapes[shape], jets, &mut |_| {});
+            i += 1;
+        }
+        chamber.height + skipped
     }
-    for i in 0..rock.sprite.len() {
-        if chamber[y - 1] & rock.sprite[i] << (7 - x - rock.width) > 0 {
-            return false;
+
+    /// Lets `count` rocks fall one step at a time, to be replayed.
+    pub fn replay<'a>(&'a self, jets: &Jets, count: usize) -> Replay<'a> {
+        let mut chamber = Chamber {
+            width: self.width,
+            ..Chamber::default()
+        };
+        let mut events = Vec::new();
+        for i in 0..count {
+            let shape = i % self.shapes.len();
+            chamber.drop(shape, &self.shapes[shape], jets, &mut |event| {
+                events.push(event);
+            });
+        }
+        Replay {
+            tower: self,
+            events,
         }
     }
-    true
 }
 
-fn print_chamber(chamber: &Object, rock: &Rock, x: usize, y: usize) {
-    for (rowno, row) in chamber.iter().enumerate().rev() {
-        print!("{:4} ", rowno);
-        for i in 0..7 {
-            if rowno >= y
-                && rowno < y + rock.sprite.len()
-                && (rock.sprite[rowno - y] << (7 - x - rock.width) & (1 << (6 - i))) > 0
-            {
-                print!("@");
-            } else if (*row & (1 << (6 - i))) > 0 {
-                print!("#");
-            } else {
-                print!(".");
+/// What happens to a falling rock, with its position afterwards as the
+/// column of its left edge and the row of its bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Rock number `shape` appears.
+    Spawn {
+        shape: usize,
+        x: usize,
+        y: usize,
+    },
+    /// A gust pushes the rock left or right, which it may not be able to.
+    Push {
+        left: bool,
+        x: usize,
+    },
+    Fall {
+        y: usize,
+    },
+    /// The rock comes to rest where it is.
+    Settle,
+}
+
+/// The events of some falling rocks, and the chamber after each of them.
+pub struct Replay<'a> {
+    tower: &'a Tower,
+    pub events: Vec<Event>,
+}
+
+impl Replay<'_> {
+    /// The chamber after the first `events` events, or all of them if there
+    /// are fewer, with the rock that is still falling if any, showing at
+    /// most `limit` rows from the top.
+    pub fn frame(&self, events: usize, limit: usize) -> String {
+        let mut player = Player::new(self.tower);
+        for event in &self.events[..events.min(self.events.len())] {
+            player.play(*event);
+        }
+        player.render(limit)
+    }
+
+    /// Plays every frame on a terminal, replacing the previous frame and
+    /// waiting `delay` in between.
+    pub fn animate(&self, out: &mut impl Write, limit: usize, delay: Duration) -> io::Result<()> {
+        let mut player = Player::new(self.tower);
+        let mut show = |player: &Player| -> io::Result<()> {
+            write!(out, "\x1b[H\x1b[2J{}", player.render(limit))?;
+            out.flush()?;
+            thread::sleep(delay);
+            Ok(())
+        };
+        show(&player)?;
+        for event in &self.events {
+            if player.play(*event) {
+                show(&player)?;
             }
         }
-        println!();
+        Ok(())
     }
 }
 
-pub fn impl1(filename: &str) -> usize {
-    let gusts = read_to_string(filename).unwrap().chars().collect_vec();
-    let mut gustindex: usize = 0;
-    let mut chamber: Object = Vec::new();
-    let rocks = make_shapes();
+/// The chamber as events are played one at a time, with the falling rock
+/// as its shape and position.
+struct Player<'a> {
+    tower: &'a Tower,
+    chamber: Chamber,
+    falling: Option<(usize, usize, usize)>,
+}
+
+impl<'a> Player<'a> {
+    fn new(tower: &'a Tower) -> Self {
+        Player {
+            tower,
+            chamber: Chamber {
+                width: tower.width,
+                ..Chamber::default()
+            },
+            falling: None,
+        }
+    }
 
-    for i in 0..2022 {
-        let top: i32 = if let Some(rowno) = chamber.iter().rposition(|row| *row > 0) {
-            i32::try_from(rowno).unwrap()
-        } else {
-            -1
+    /// Applies `event`, or skips it and returns `false` if it cannot happen,
+    /// such as a push with no rock falling or a rock outside the chamber or
+    /// above where rocks appear.
+    fn play(&mut self, event: Event) -> bool {
+        let falling = match (event, self.falling) {
+            (Event::Spawn { shape, x, y }, _)
+            | (Event::Push { x, .. }, Some((shape, _, y)))
+            | (Event::Fall { y }, Some((shape, x, _))) => (shape, x, y),
+            (Event::Settle, Some((shape, x, y))) => {
+                self.chamber.settle(&self.tower.shapes[shape], x, y);
+                self.falling = None;
+                return true;
+            }
+            (_, None) => return false,
         };
-        // println!("top = {}", top);
-        chamber.resize(usize::try_from(top + 8).unwrap(), 0);
-        println!("new size of chamber = {}", chamber.len());
-        let mut x = 2;
-        let mut y = usize::try_from(top + 4).unwrap();
-        let rock = &rocks[i % 5];
-        println!("Rock {} begins falling at {}, {}", i, x, y);
-        print_chamber(&chamber, rock, x, y);
+        let (shape, x, y) = falling;
+        match self.tower.shapes.get(shape) {
+            Some(rock) if x + rock.width <= self.tower.width && y <= self.chamber.height + 3 => {
+                self.falling = Some(falling);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn render(&self, limit: usize) -> String {
+        let falling = self
+            .falling
+            .map(|(shape, x, y)| (&self.tower.shapes[shape], x, y));
+        render(&self.chamber, falling, limit)
+    }
+}
+
+fn fits(rock: &Rock, x: usize, y: usize, chamber: &Chamber) -> bool {
+    x + rock.width <= chamber.width
+        && rock.sprite.iter().enumerate().all(|(i, row)| {
+            chamber
+                .rows
+                .get(y + i)
+                .is_none_or(|rows| rows & row << x == 0)
+        })
+}
+
+/// Draws the chamber like the puzzle, top row first, with the falling rock
+/// if any and at most `limit` rows.
+fn render(chamber: &Chamber, falling: Option<(&Rock, usize, usize)>, limit: usize) -> String {
+    let top = falling.map_or(chamber.height, |(rock, _, y)| {
+        chamber.height.max(y + rock.sprite.len())
+    });
+    let mut out = String::new();
+    for y in (top.saturating_sub(limit)..top).rev() {
+        let rock = falling
+            .filter(|(rock, _, bottom)| (*bottom..bottom + rock.sprite.len()).contains(&y))
+            .map_or(0, |(rock, x, bottom)| rock.sprite[y - bottom] << x);
+        out.push('|');
+        for x in 0..chamber.width {
+            out.push(if rock & 1 << x != 0 {
+                '@'
+            } else if chamber.rows.get(y).is_some_and(|row| row & 1 << x != 0) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        out.push_str("|\n");
+    }
+    if top <= limit {
+        out.push('+');
+        out.push_str(&"-".repeat(chamber.width));
+        out.push_str("+\n");
+    }
+    out
+}
+
+/// The jet pattern, telling for each gust in turn whether it pushes left.
+/// It is never empty, and only made by [`Day17::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jets {
+    left: Vec<bool>,
+}
+
+fn parse(input: &str) -> Result<Jets, ParseError> {
+    let Some(line) = lines(17, input).next() else {
+        return Err(ParseError::input(17, "no jet pattern"));
+    };
+    let line = line.trim();
+    if let Some(i) = line.text.find(|c| c != '<' && c != '>') {
+        return Err(line.error_at(i, "expected '<' or '>'"));
+    }
+    if line.text.is_empty() {
+        return Err(ParseError::input(17, "no jet pattern"));
+    }
+    Ok(Jets {
+        left: line.text.chars().map(|c| c == '<').collect(),
+    })
+}
+
+/// Rows at the top of the tower that are taken to decide what happens to
+/// the next rocks.
+const SURFACE: usize = 64;
+
+/// The rows of settled rock, with the tallest column at `height`, and the
+/// next gust to push a rock.
+#[derive(Default)]
+struct Chamber {
+    rows: Object,
+    width: usize,
+    height: usize,
+    gust: usize,
+}
+
+impl Chamber {
+    /// Lets rock number `shape` fall until it settles, telling `observe`
+    /// about every step. The tower only needs the height and passes a
+    /// closure that does nothing, which costs nothing.
+    fn drop(&mut self, shape: usize, rock: &Rock, jets: &Jets, observe: &mut impl FnMut(Event)) {
+        self.rows.resize(self.height + 3 + rock.sprite.len(), 0);
+        let (mut x, mut y) = (2, self.height + 3);
+        observe(Event::Spawn { shape, x, y });
         loop {
-            let gust = gusts[gustindex];
-            println!("{}", gust);
-            gustindex = (gustindex + 1) % gusts.len();
-            if gust == '<' && can_move_left(rock, x, y, &chamber) {
-                // println!("push left");
+            let left = jets.left[self.gust];
+            self.gust = (self.gust + 1) % jets.left.len();
+            if left && x > 0 && fits(rock, x - 1, y, self) {
                 x -= 1;
-            } else if gust == '>' && can_move_right(rock, x, y, &chamber) {
-                // println!("push right");
+            } else if !left && fits(rock, x + 1, y, self) {
                 x += 1;
             }
-            if can_move_down(rock, x, y, &chamber) {
-                // println!("falls down");
-                y -= 1;
-            } else {
-                println!("hit rock at {}, {}", x, y);
-                print_chamber(&chamber, rock, x, y);
-                for i in 0..rock.sprite.len() {
-                    chamber[y + i] |= rock.sprite[i] << (7 - x - rock.width);
-                }
-                // println!("chamber = {:?}", chamber);
-                println!(
-                    "after {} rocks, tower height = {}",
-                    i + 1,
-                    chamber.iter().rposition(|row| *row > 0).unwrap() + 1
-                );
+            observe(Event::Push { left, x });
+            if y == 0 || !fits(rock, x, y - 1, self) {
                 break;
             }
+            y -= 1;
+            observe(Event::Fall { y });
+        }
+        self.settle(rock, x, y);
+        observe(Event::Settle);
+    }
+
+    fn settle(&mut self, rock: &Rock, x: usize, y: usize) {
+        self.rows
+            .resize(self.rows.len().max(y + rock.sprite.len()), 0);
+        for (i, row) in rock.sprite.iter().enumerate() {
+            self.rows[y + i] |= row << x;
         }
+        self.height = self.height.max(y + rock.sprite.len());
+    }
+
+    /// How far down from the top the rock in each column is, looking no
+    /// further than `SURFACE` rows down.
+    fn surface(&self) -> Vec<usize> {
+        let top = &self.rows[self.height.saturating_sub(SURFACE)..self.height];
+        (0..self.width)
+            .map(|column| {
+                top.iter()
+                    .rev()
+                    .position(|row| row & 1 << column != 0)
+                    .unwrap_or(SURFACE)
+            })
+            .collect()
+    }
+}
+
+pub fn tower_height(jets: &Jets, count: usize) -> usize {
+    Tower::default().height(jets, count)
+}
+
+pub fn impl1(filename: &str) -> Result<usize, Error> {
+    Ok(tower_height(&Day17::parse_file(filename)?, 2022))
+}
+
+pub fn impl2(filename: &str) -> Result<usize, Error> {
+    Ok(tower_height(
+        &Day17::parse_file(filename)?,
+        1_000_000_000_000,
+    ))
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    type Input = Jets;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(jets: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(tower_height(jets, 2022)))
+    }
+
+    fn part2(jets: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(tower_height(jets, 1_000_000_000_000)))
     }
-    chamber.iter().rposition(|row| *row > 0).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::impl1;
+    use super::{impl1, impl2, Event, Tower};
+    use crate::Solution;
+    use std::time::Duration;
     use test_case::test_case;
 
     #[test_case("../testinput/day17.txt", 3068; "on test input")]
-    // #[test_case("../input/day17.txt", 0; "on real input")]
+    #[test_case("../input/day17.txt", 3159; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(impl1(filename), expected);
+        assert_eq!(impl1(filename).unwrap(), expected);
+    }
+
+    #[test_case("../testinput/day17.txt", 1_514_285_714_288; "on test input")]
+    #[test_case("../input/day17.txt", 1_566_272_189_352; "on real input")]
+    fn part2(filename: &str, expected: usize) {
+        assert_eq!(impl2(filename).unwrap(), expected);
+    }
+
+    #[test_case("", "day 17: no jet pattern"; "empty")]
+    #[test_case("\n", "day 17: no jet pattern"; "blank")]
+    #[test_case("<>=\n", "day 17, line 1, column 3: expected '<' or '>', found '='"; "invalid")]
+    fn invalid_jets(input: &str, expected: &str) {
+        assert_eq!(
+            super::Day17::parse(input).unwrap_err().to_string(),
+            expected
+        );
+    }
+
+    #[test]
+    fn custom_tower() {
+        let jets = super::Day17::parse("<>>").unwrap();
+        let tower = Tower::new("\n.#\n##\n\n###\n", 5).unwrap();
+        assert_eq!(tower.height(&jets, 0), 0);
+        assert_eq!(tower.height(&jets, 1), 2);
+        assert_eq!(tower.height(&jets, 2), 3);
+        let wide = Tower::new(&"#".repeat(62), 64).unwrap();
+        assert_eq!(wide.height(&jets, 1_000_000), 1_000_000);
+        // Only the blank rows around a rock are dropped, not the gap in it.
+        let gap = Tower::new("...\n#.#\n...\n#.#\n...\n", 5).unwrap();
+        assert_eq!(gap.shapes[0].sprite, [0b101, 0, 0b101]);
+        assert_eq!(gap.height(&jets, 1), 3);
+        assert_eq!(
+            Tower::new("#.\n#x", 7).err().unwrap().to_string(),
+            "day 17, line 2, column 2: expected '#' or '.', found 'x'"
+        );
+        assert_eq!(
+            Tower::new("####", 5).err().unwrap().to_string(),
+            "day 17: rock too wide for the chamber"
+        );
+    }
+    #[test]
+    fn replay() {
+        let jets = super::Day17::parse_file("../testinput/day17.txt").unwrap();
+        let tower = Tower::default();
+        let replay = tower.replay(&jets, 2);
+        assert_eq!(
+            replay.events[..9],
+            [
+                Event::Spawn {
+                    shape: 0,
+                    x: 2,
+                    y: 3
+                },
+                Event::Push { left: false, x: 3 },
+                Event::Fall { y: 2 },
+                Event::Push { left: false, x: 3 },
+                Event::Fall { y: 1 },
+                Event::Push { left: false, x: 3 },
+                Event::Fall { y: 0 },
+                Event::Push { left: true, x: 2 },
+                Event::Settle,
+            ]
+        );
+        assert_eq!(replay.frame(0, 10), "+-------+\n");
+        assert_eq!(
+            replay.frame(2, 10),
+            "|...@@@@|\n|.......|\n|.......|\n|.......|\n+-------+\n"
+        );
+        assert_eq!(replay.frame(9, 10), "|..####.|\n+-------+\n");
+        assert_eq!(
+            replay.frame(10, 10),
+            "|...@...|\n|..@@@..|\n|...@...|\n|.......|\n|.......|\n|.......|\n|..####.|\n+-------+\n"
+        );
+        assert_eq!(replay.frame(10, 2), "|...@...|\n|..@@@..|\n");
+        assert_eq!(replay.frame(replay.events.len(), 10).lines().count(), 5);
+        assert_eq!(replay.frame(usize::MAX, 10).lines().count(), 5);
+
+        let mut out = Vec::new();
+        replay.animate(&mut out, 10, Duration::ZERO).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("\x1b[2J").count(), replay.events.len() + 1);
+        assert!(out.ends_with(&replay.frame(usize::MAX, 10)));
+    }
+
+    #[test]
+    fn impossible_events() {
+        let tower = Tower::default();
+        let mut replay = tower.replay(&super::Day17::parse(">").unwrap(), 0);
+        replay.events = vec![
+            Event::Fall { y: 2 },
+            Event::Spawn {
+                shape: 9,
+                x: 0,
+                y: 3,
+            },
+            Event::Spawn {
+                shape: 0,
+                x: 5,
+                y: 3,
+            },
+            Event::Spawn {
+                shape: 0,
+                x: 0,
+                y: usize::MAX,
+            },
+            Event::Settle,
+            Event::Spawn {
+                shape: 0,
+                x: 3,
+                y: 0,
+            },
+            Event::Push { left: false, x: 4 },
+            Event::Settle,
+        ];
+        assert_eq!(replay.frame(usize::MAX, 10), "|...####|\n+-------+\n");
     }
 }
@@ -1,8 +1,9 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use fxhash::FxHashSet as HashSet;
-use std::{collections::VecDeque, fs::read_to_string};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
-struct Coord {
+pub struct Coord {
     x: i32,
     y: i32,
     z: i32,
@@ -37,23 +38,24 @@ fn neighbours(cube: &Coord) -> Vec<Coord> {
     ]
 }
 
-fn parse_file(filename: &str) -> HashSet<Coord> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+fn parse(input: &str) -> Result<HashSet<Coord>, ParseError> {
+    let droplet: HashSet<Coord> = lines(18, input)
         .map(|line| {
-            let mut splits = line.split(',');
-            Coord {
-                x: splits.next().unwrap().parse().unwrap(),
-                y: splits.next().unwrap().parse().unwrap(),
-                z: splits.next().unwrap().parse().unwrap(),
-            }
+            let [x, y, z] = line.split(line.text, ",")?;
+            Ok(Coord {
+                x: line.number(x)?,
+                y: line.number(y)?,
+                z: line.number(z)?,
+            })
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+    if droplet.is_empty() {
+        return Err(ParseError::input(18, "no cubes"));
+    }
+    Ok(droplet)
 }
 
-pub fn surface_area(filename: &str) -> usize {
-    let droplet = parse_file(filename);
+fn area(droplet: &HashSet<Coord>) -> usize {
     droplet
         .iter()
         .map(|d| {
@@ -65,8 +67,7 @@ pub fn surface_area(filename: &str) -> usize {
         .sum()
 }
 
-pub fn external_surface_area(filename: &str) -> usize {
-    let droplet = parse_file(filename);
+fn external_area(droplet: &HashSet<Coord>) -> usize {
     let x_min = droplet.iter().map(|c| c.x).min().unwrap();
     let x_max = droplet.iter().map(|c| c.x).max().unwrap();
     let y_min = droplet.iter().map(|c| c.y).min().unwrap();
@@ -108,19 +109,54 @@ pub fn external_surface_area(filename: &str) -> usize {
         .sum()
 }
 
+pub fn surface_area(filename: &str) -> Result<usize, Error> {
+    Ok(area(&Day18::parse_file(filename)?))
+}
+
+pub fn external_surface_area(filename: &str) -> Result<usize, Error> {
+    Ok(external_area(&Day18::parse_file(filename)?))
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Input = HashSet<Coord>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(droplet: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(area(droplet)))
+    }
+
+    fn part2(droplet: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(external_area(droplet)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{external_surface_area, surface_area};
+    use super::{external_surface_area, parse, surface_area};
     use test_case::test_case;
 
+    #[test]
+    fn no_cubes() {
+        assert_eq!(parse("").unwrap_err().to_string(), "day 18: no cubes");
+    }
+
     #[test_case("../testinput/day18.txt", 64; "on test input")]
     #[test_case("../input/day18.txt", 4580; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(surface_area(filename), expected);
+        assert_eq!(surface_area(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day18.txt", 58; "on test input")]
     #[test_case("../input/day18.txt", 2610; "on real input")]
     fn part2(filename: &str, expected: usize) {
-        assert_eq!(external_surface_area(filename), expected);
+        assert_eq!(external_surface_area(filename).unwrap(), expected);
     }
 }
@@ -1,267 +1,549 @@
-#![allow(clippy::too_many_lines)]
-
-use fxhash::FxHashSet as HashSet;
-use itertools::Itertools;
+use crate::{input::lines, Error, ParseError, Solution};
 use std::{
     cmp::Ordering,
-    collections::VecDeque,
-    fs::read_to_string,
-    ops::{Add, Sub},
+    fmt::{self, Write as _},
+    ops::{Add, Index, IndexMut, Mul},
 };
 
-#[derive(Debug, Clone)]
-struct Blueprint {
-    id: u32,
-    ore_cost: Materials,
-    clay_cost: Materials,
-    obsidian_cost: Materials,
-    geode_cost: Materials,
-}
+/// An amount of each of `N` kinds of materials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Materials<const N: usize>(pub [u32; N]);
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Materials {
-    ore: u32,
-    clay: u32,
-    obsidian: u32,
+impl<const N: usize> Default for Materials<N> {
+    fn default() -> Self {
+        Materials([0; N])
+    }
 }
-impl Ord for Materials {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if self.ore < other.ore || self.clay < other.clay || self.obsidian < other.obsidian {
-            Ordering::Less
-        } else if self.ore > other.ore || self.clay > other.clay || self.obsidian > other.obsidian {
-            Ordering::Greater
-        } else {
-            Ordering::Equal
+
+impl<const N: usize> Materials<N> {
+    /// One of the kind `kind` and none of the others.
+    pub fn unit(kind: usize) -> Self {
+        let mut materials = Materials::default();
+        materials[kind] = 1;
+        materials
+    }
+
+    /// The materials left after paying `other`, or `None` if there is not
+    /// enough of some kind.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let mut left = *self;
+        for (left, other) in left.0.iter_mut().zip(other.0) {
+            *left = left.checked_sub(other)?;
         }
+        Some(left)
     }
 }
-impl PartialOrd for Materials {
+
+/// Materials are only ordered when one has at least as much of every kind
+/// as the other.
+impl<const N: usize> PartialOrd for Materials<N> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+        self.0
+            .iter()
+            .zip(other.0)
+            .try_fold(Ordering::Equal, |order, (a, b)| match (order, a.cmp(&b)) {
+                (order, Ordering::Equal) => Some(order),
+                (Ordering::Equal, cmp) => Some(cmp),
+                (order, cmp) if order == cmp => Some(order),
+                _ => None,
+            })
     }
 }
-impl<'a, 'b> Sub<&'b Materials> for &'a Materials {
-    type Output = Materials;
-
-    fn sub(self, other: &'b Materials) -> Self::Output {
-        Materials {
-            ore: self.ore - other.ore,
-            clay: self.clay - other.clay,
-            obsidian: self.obsidian - other.obsidian,
+
+impl<const N: usize> Add for Materials<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a += b;
         }
+        self
     }
 }
-impl<'a, 'b> Add<&'b Materials> for &'a Materials {
-    type Output = Materials;
-
-    fn add(self, other: &'b Materials) -> Self::Output {
-        Materials {
-            ore: self.ore + other.ore,
-            clay: self.clay + other.clay,
-            obsidian: self.obsidian + other.obsidian,
+
+impl<const N: usize> Mul<u32> for Materials<N> {
+    type Output = Self;
+
+    fn mul(mut self, factor: u32) -> Self {
+        for a in &mut self.0 {
+            *a *= factor;
         }
+        self
     }
 }
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
-struct QueueItem {
-    id: u32,
-    minute: u32,
-    purse: Materials,
-    ore_robots: u32,
-    clay_robots: u32,
-    obsidian_robots: u32,
-    geode_robots: u32,
-    geodes: u32,
+impl<const N: usize> Index<usize> for Materials<N> {
+    type Output = u32;
+
+    fn index(&self, kind: usize) -> &u32 {
+        &self.0[kind]
+    }
 }
 
-// blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 14 clay. Each geode robot costs 3 ore and 16 obsidian.
-fn parse_file(filename: &str) -> Vec<Blueprint> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+impl<const N: usize> IndexMut<usize> for Materials<N> {
+    fn index_mut(&mut self, kind: usize) -> &mut u32 {
+        &mut self.0[kind]
+    }
+}
+
+/// The costs of a robot collecting each of `N` kinds of materials, where the
+/// last kind is the one to collect as much as possible of. The puzzle has
+/// ore, clay, obsidian and geodes.
+#[derive(Debug, Clone)]
+pub struct Blueprint<const N: usize = 4> {
+    pub id: u32,
+    pub costs: [Materials<N>; N],
+}
+
+const BLUEPRINT: &str = "Blueprint {}: \
+    Each ore robot costs {} ore. \
+    Each clay robot costs {} ore. \
+    Each obsidian robot costs {} ore and {} clay. \
+    Each geode robot costs {} ore and {} obsidian.";
+
+/// Parses a blueprint per line.
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    lines(19, input)
         .map(|line| {
-            let split = line.split(' ').collect_vec();
-            Blueprint {
-                id: split[1][0..split[1].len() - 1].parse::<u32>().unwrap(),
-                ore_cost: Materials {
-                    ore: split[6].parse().unwrap(),
-                    clay: 0,
-                    obsidian: 0,
-                },
-                clay_cost: Materials {
-                    ore: split[12].parse().unwrap(),
-                    clay: 0,
-                    obsidian: 0,
-                },
-                obsidian_cost: Materials {
-                    ore: split[18].parse().unwrap(),
-                    clay: split[21].parse().unwrap(),
-                    obsidian: 0,
-                },
-                geode_cost: Materials {
-                    ore: split[27].parse().unwrap(),
-                    clay: 0,
-                    obsidian: split[30].parse().unwrap(),
-                },
-            }
+            let [id, ore, clay, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian] =
+                line.scan(BLUEPRINT)?;
+            Ok(Blueprint {
+                id: line.number(id)?,
+                costs: [
+                    Materials([line.number(ore)?, 0, 0, 0]),
+                    Materials([line.number(clay)?, 0, 0, 0]),
+                    Materials([
+                        line.number(obsidian_ore)?,
+                        line.number(obsidian_clay)?,
+                        0,
+                        0,
+                    ]),
+                    Materials([line.number(geode_ore)?, 0, line.number(geode_obsidian)?, 0]),
+                ],
+            })
         })
         .collect()
 }
 
-fn largest_number_of_geodes(blueprint: &Blueprint) -> u32 {
-    let mut queue = VecDeque::<QueueItem>::new();
-    let mut next_id: u32 = 0;
-
-    queue.push_back(QueueItem {
-        id: next_id,
-        minute: 1,
-        purse: Materials {
-            ore: 0,
-            clay: 0,
-            obsidian: 0,
-        },
-        ore_robots: 1,
-        clay_robots: 0,
-        obsidian_robots: 0,
-        geode_robots: 0,
-        geodes: 0,
-    });
-
-    let mut max_geodes = 0;
-    let mut visited: HashSet<QueueItem> = HashSet::default();
-
-    while !queue.is_empty() {
-        let item = queue.pop_front().unwrap();
-        if visited.contains(&item) {
-            continue;
+/// Depth first search over which robot to build next, waiting for it as
+/// long as needed, with the most of the last kind found so far.
+struct Search<'a, const N: usize> {
+    blueprint: &'a Blueprint<N>,
+    /// No more robots of a kind are needed than can be spent in a minute,
+    /// since only one robot is built per minute.
+    caps: Materials<N>,
+    best: u32,
+    /// The robots built on the way to the current state, and to the best
+    /// one, with the minutes left after building them.
+    route: Vec<(usize, u32)>,
+    best_route: Vec<(usize, u32)>,
+}
+
+/// Minutes until the materials in `purse` collected by `robots` cover
+/// `cost`, if they ever do.
+fn wait<const N: usize>(
+    cost: &Materials<N>,
+    purse: &Materials<N>,
+    robots: &Materials<N>,
+) -> Option<u32> {
+    (0..N)
+        .map(
+            |kind| match (cost[kind].saturating_sub(purse[kind]), robots[kind]) {
+                (0, _) => Some(0),
+                (_, 0) => None,
+                (missing, robots) => Some(missing.div_ceil(robots)),
+            },
+        )
+        .try_fold(0, |wait, minutes| Some(wait.max(minutes?)))
+}
+
+impl<const N: usize> Search<'_, N> {
+    /// Robots collecting the last kind are not counted among the robots,
+    /// instead what they collect by the end is added when they are built.
+    fn search(&mut self, minutes: u32, robots: &Materials<N>, purse: &Materials<N>, most: u32) {
+        if most > self.best {
+            self.best = most;
+            self.best_route.clone_from(&self.route);
         }
-        visited.insert(item.clone());
-        //  println!("{}: minute {} - {:?}", item.id, item.minute, item);
-        let geodes = item.geodes + item.geode_robots;
-        //  println!("  geodes now {}", geodes);
-        if geodes > max_geodes {
-            //  println!("  new max_geodes = {}", geodes);
-            max_geodes = geodes;
+        // Even such a robot built every remaining minute would not do.
+        if most + minutes * minutes.saturating_sub(1) / 2 <= self.best {
+            return;
         }
-        if item.minute >= 24 {
-            continue;
+        for robot in (0..N).rev() {
+            let cost = &self.blueprint.costs[robot];
+            let Some(wait) = wait(cost, purse, robots)
+                .filter(|wait| robots[robot] < self.caps[robot] && wait + 1 < minutes)
+            else {
+                continue;
+            };
+            let left = minutes - wait - 1;
+            let purse = (*purse + *robots * (wait + 1)).checked_sub(cost).unwrap();
+            self.route.push((robot, left));
+            if robot == N - 1 {
+                self.search(left, robots, &purse, most + left);
+            } else {
+                self.search(left, &(*robots + Materials::unit(robot)), &purse, most);
+            }
+            self.route.pop();
         }
-        // if item.purse.ore > 12 || item.purse.clay > 50 {
-        //     continue;
-        // }
-
-        // if item.minute > 22 && geodes == 0 {
-        //     continue;
-        // }
-
-        let income = Materials {
-            ore: item.ore_robots,
-            clay: item.clay_robots,
-            obsidian: item.obsidian_robots,
-        };
-        //  println!("  income this minute = {:?}", income);
-        let purse = &item.purse + &income;
-        //  println!("  materials availible = {:?}", purse);
-
-        let mut built = 0;
-
-        if item.minute < 10 && item.purse >= blueprint.ore_cost && item.ore_robots < 6 {
-            built += 1;
-            next_id += 1;
-            //  println!("  {} is building ore robot", next_id);
-            queue.push_back(QueueItem {
-                id: next_id,
-                minute: item.minute + 1,
-                purse: &purse - &blueprint.ore_cost,
-                ore_robots: item.ore_robots + 1,
-                geodes,
-                ..item
-            });
+    }
+}
+
+fn search<const N: usize>(blueprint: &Blueprint<N>, minutes: u32) -> Search<'_, N> {
+    let mut caps = Materials([u32::MAX; N]);
+    for kind in 0..N - 1 {
+        caps[kind] = blueprint.costs.iter().map(|cost| cost[kind]).max().unwrap();
+    }
+    let mut search = Search {
+        blueprint,
+        caps,
+        best: 0,
+        route: Vec::new(),
+        best_route: Vec::new(),
+    };
+    search.search(minutes, &Materials::unit(0), &Materials::default(), 0);
+    search
+}
+
+/// The most of the last kind of materials that can be collected in
+/// `minutes`, starting with a robot collecting the first kind.
+pub fn largest_number_of_geodes<const N: usize>(blueprint: &Blueprint<N>, minutes: u32) -> u32 {
+    search(blueprint, minutes).best
+}
+
+/// The robots and materials at the end of a minute, counting from 1, and
+/// the kind of robot built during it if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minute<const N: usize> {
+    pub minute: u32,
+    pub built: Option<usize>,
+    pub robots: Materials<N>,
+    pub materials: Materials<N>,
+}
+
+/// How to collect the most of the last kind of materials with a blueprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation<const N: usize> {
+    pub id: u32,
+    pub geodes: u32,
+    pub minutes: Vec<Minute<N>>,
+}
+
+/// Finds the best build order like [`largest_number_of_geodes`] and plays
+/// it minute by minute.
+pub fn explain<const N: usize>(blueprint: &Blueprint<N>, minutes: u32) -> Explanation<N> {
+    let search = search(blueprint, minutes);
+    let mut builds = search.best_route.iter().peekable();
+    let mut robots = Materials::unit(0);
+    let mut materials = Materials::default();
+    let mut explanation = Explanation {
+        id: blueprint.id,
+        geodes: search.best,
+        minutes: Vec::new(),
+    };
+    for minute in 1..=minutes {
+        let built = builds
+            .next_if(|(_, left)| minutes - left == minute)
+            .map(|&(robot, _)| robot);
+        if let Some(robot) = built {
+            materials = materials.checked_sub(&blueprint.costs[robot]).unwrap();
         }
-        if item.minute < 18 && item.purse >= blueprint.clay_cost && item.clay_robots < 8 {
-            built += 1;
-            next_id += 1;
-            //  println!("  {} is building clay robot", next_id);
-            queue.push_back(QueueItem {
-                id: next_id,
-                minute: item.minute + 1,
-                purse: &purse - &blueprint.clay_cost,
-                clay_robots: item.clay_robots + 1,
-                geodes,
-                ..item
-            });
+        materials = materials + robots;
+        if let Some(robot) = built {
+            robots[robot] += 1;
         }
-        if item.minute < 21 && item.purse >= blueprint.obsidian_cost && item.obsidian_robots < 9 {
-            built += 1;
-            next_id += 1;
-            //  println!("  {} is building obsidian robot", next_id);
-            queue.push_back(QueueItem {
-                id: next_id,
-                minute: item.minute + 1,
-                purse: &purse - &blueprint.obsidian_cost,
-                obsidian_robots: item.obsidian_robots + 1,
-                geodes,
-                ..item
+        explanation.minutes.push(Minute {
+            minute,
+            built,
+            robots,
+            materials,
+        });
+    }
+    explanation
+}
+
+/// The name of a kind of materials, as in the puzzle when there are four.
+fn kind(kind: usize, kinds: usize) -> String {
+    match (kinds, kind) {
+        (4, 0) => "ore".to_string(),
+        (4, 1) => "clay".to_string(),
+        (4, 2) => "obsidian".to_string(),
+        (4, 3) => "geode".to_string(),
+        _ => format!("kind {kind}"),
+    }
+}
+
+impl<const N: usize> Explanation<N> {
+    /// The robots built and when, e.g. `clay 3, clay 5, obsidian 11`.
+    pub fn builds(&self) -> String {
+        self.minutes
+            .iter()
+            .filter_map(|minute| {
+                let robot = minute.built?;
+                Some(format!("{} {}", kind(robot, N), minute.minute))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A line per minute with the robot built and the robots and materials of
+/// each kind at the end of it.
+impl<const N: usize> fmt::Display for Explanation<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |materials: &Materials<N>| {
+            (0..N)
+                .map(|i| format!("{} {}", materials[i], kind(i, N)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "Blueprint {}: {}", self.id, geodes(self.geodes))?;
+        for minute in &self.minutes {
+            let built = minute.built.map_or(String::new(), |robot| {
+                format!("build {} robot; ", kind(robot, N))
             });
+            writeln!(
+                f,
+                "  minute {:2}: {built}robots {}; materials {}",
+                minute.minute,
+                counts(&minute.robots),
+                counts(&minute.materials)
+            )?;
         }
-        if item.purse >= blueprint.geode_cost {
-            built += 1;
-            next_id += 1;
-            //  println!("  {} is building geode robot", next_id);
-            queue.push_back(QueueItem {
-                id: next_id,
-                minute: item.minute + 1,
-                purse: &purse - &blueprint.geode_cost,
-                geode_robots: item.geode_robots + 1,
-                geodes,
-                ..item
-            });
+        Ok(())
+    }
+}
+
+fn geodes(count: u32) -> String {
+    if count == 1 {
+        "1 geode".to_string()
+    } else {
+        format!("{count} geodes")
+    }
+}
+
+/// A line per blueprint of `part` with the most geodes, the quality level
+/// for part 1, and the robots built, then the answer: the sum of the
+/// quality levels in 24 minutes, or the product of the geodes with the
+/// first three blueprints in 32 minutes.
+pub fn report(blueprints: &[Blueprint], part: u8) -> String {
+    let (blueprints, minutes) = if part == 2 {
+        (&blueprints[..blueprints.len().min(3)], 32)
+    } else {
+        (blueprints, 24)
+    };
+    let mut report = String::new();
+    let mut answer = u32::from(part == 2);
+    for blueprint in blueprints {
+        let explanation = explain(blueprint, minutes);
+        let mut builds = explanation.builds();
+        if builds.is_empty() {
+            builds = "no robots".to_string();
         }
-        if built < 4 {
-            // save materials for more expensive build
-            next_id += 1;
-            //  println!("  {} is stockpiling materials", next_id);
-            queue.push_back(QueueItem {
-                id: next_id,
-                minute: item.minute + 1,
-                purse,
-                geodes,
-                ..item
-            });
+        let id = blueprint.id;
+        if part == 2 {
+            answer *= explanation.geodes;
+            _ = writeln!(
+                report,
+                "Blueprint {id}: {}: {builds}",
+                geodes(explanation.geodes)
+            );
+        } else {
+            let quality = id * explanation.geodes;
+            answer += quality;
+            _ = writeln!(
+                report,
+                "Blueprint {id}: {}, quality level {quality}: {builds}",
+                geodes(explanation.geodes)
+            );
         }
-        // //  println!("queue.len now {}", queue.len());
     }
-    println!("returning max_geodes = {}", max_geodes);
-    max_geodes
+    if part == 2 {
+        _ = writeln!(report, "Product of geodes: {answer}");
+    } else {
+        _ = writeln!(report, "Sum of quality levels: {answer}");
+    }
+    report
 }
 
-pub fn impl1(filename: &str) -> u32 {
-    parse_file(filename)
+fn quality_levels(blueprints: &[Blueprint]) -> u32 {
+    blueprints
         .iter()
-        .map(|blueprint| blueprint.id * largest_number_of_geodes(blueprint))
+        .map(|blueprint| blueprint.id * largest_number_of_geodes(blueprint, 24))
         .sum()
 }
 
+fn first_three(blueprints: &[Blueprint]) -> u32 {
+    blueprints
+        .iter()
+        .take(3)
+        .map(|blueprint| largest_number_of_geodes(blueprint, 32))
+        .product()
+}
+
+pub fn impl1(filename: &str) -> Result<u32, Error> {
+    Ok(quality_levels(&Day19::parse_file(filename)?))
+}
+
+pub fn impl2(filename: &str) -> Result<u32, Error> {
+    Ok(first_three(&Day19::parse_file(filename)?))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+
+    type Input = Vec<Blueprint>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(blueprints: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(quality_levels(blueprints)))
+    }
+
+    fn part2(blueprints: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(first_three(blueprints)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{impl1, largest_number_of_geodes, parse_file};
+    use super::{
+        explain, impl1, impl2, largest_number_of_geodes, parse, report, Blueprint, Day19, Materials,
+    };
+    use crate::Solution;
     use test_case::test_case;
 
     #[test]
     fn lnog0() {
-        let bps = parse_file("../testinput/day19.txt");
-        assert_eq!(9, largest_number_of_geodes(&bps[0]));
+        let bps = Day19::parse_file("../testinput/day19.txt").unwrap();
+        assert_eq!(9, largest_number_of_geodes(&bps[0], 24));
+        assert_eq!(56, largest_number_of_geodes(&bps[0], 32));
     }
     #[test]
     fn lnog1() {
-        let bps = parse_file("../testinput/day19.txt");
-        assert_eq!(12, largest_number_of_geodes(&bps[1]));
+        let bps = Day19::parse_file("../testinput/day19.txt").unwrap();
+        assert_eq!(12, largest_number_of_geodes(&bps[1], 24));
+        assert_eq!(62, largest_number_of_geodes(&bps[1], 32));
+    }
+
+    #[test]
+    fn explanation() {
+        let bps = Day19::parse_file("../testinput/day19.txt").unwrap();
+        let explanation = explain(&bps[0], 24);
+        assert_eq!(explanation.geodes, 9);
+        assert_eq!(explanation.minutes.len(), 24);
+        let last = explanation.minutes.last().unwrap();
+        assert_eq!(last.materials[3], 9);
+        assert_eq!(last.robots[3], 2);
+        // Every minute pays for its robot from the materials before it.
+        let mut materials = Materials::default();
+        for minute in &explanation.minutes {
+            let mut robots = minute.robots;
+            if let Some(robot) = minute.built {
+                robots[robot] -= 1;
+                materials = materials.checked_sub(&bps[0].costs[robot]).unwrap();
+            }
+            materials = materials + robots;
+            assert_eq!(minute.materials, materials);
+        }
+        let text = explanation.to_string();
+        assert!(text.starts_with("Blueprint 1: 9 geodes\n"));
+        assert!(text.contains(
+            "  minute 24: robots 1 ore, 4 clay, 2 obsidian, 2 geode; \
+             materials 6 ore, 41 clay, 8 obsidian, 9 geode\n"
+        ));
+        assert_eq!(
+            report(&bps, 1),
+            "Blueprint 1: 9 geodes, quality level 9: clay 3, clay 5, clay 7, obsidian 11, \
+             clay 12, obsidian 15, geode 18, geode 21\n\
+             Blueprint 2: 12 geodes, quality level 24: ore 3, ore 5, clay 6, clay 7, clay 8, \
+             clay 9, clay 10, obsidian 11, obsidian 13, obsidian 14, obsidian 16, obsidian 17, \
+             geode 18, obsidian 19, geode 20, geode 22\n\
+             Sum of quality levels: 33\n"
+        );
+        let report = report(&bps, 2);
+        assert!(!report.contains("quality"));
+        assert!(report.starts_with("Blueprint 1: 56 geodes: "));
+        assert!(report.ends_with("Product of geodes: 3472\n"));
+    }
+
+    #[test]
+    fn one_geode() {
+        // Geode robots cost so much obsidian that there is only time for one.
+        let blueprint = Blueprint {
+            id: 3,
+            costs: [
+                Materials([4, 0, 0, 0]),
+                Materials([2, 0, 0, 0]),
+                Materials([3, 14, 0, 0]),
+                Materials([2, 0, 20, 0]),
+            ],
+        };
+        assert_eq!(largest_number_of_geodes(&blueprint, 24), 1);
+        assert!(report(&[blueprint], 1).starts_with("Blueprint 3: 1 geode, quality level 3: "));
+    }
+
+    #[test]
+    fn materials() {
+        let (a, b) = (Materials([1, 2, 3]), Materials([1, 3, 3]));
+        assert!(a < b);
+        assert_eq!(a.partial_cmp(&a), Some(std::cmp::Ordering::Equal));
+        assert_eq!(
+            Materials([2, 1, 0]).partial_cmp(&Materials([1, 2, 0])),
+            None
+        );
+        assert_eq!(b.checked_sub(&a), Some(Materials([0, 1, 0])));
+        assert_eq!(a.checked_sub(&b), None);
+        assert_eq!(a + b * 2, Materials([3, 8, 9]));
+    }
+
+    #[test]
+    fn more_kinds() {
+        // The first test blueprint with crystal between obsidian and geodes,
+        // which geode robots need one of and crystal robots cost 2 ore.
+        let blueprint = Blueprint {
+            id: 1,
+            costs: [
+                Materials([4, 0, 0, 0, 0]),
+                Materials([2, 0, 0, 0, 0]),
+                Materials([3, 14, 0, 0, 0]),
+                Materials([2, 0, 0, 0, 0]),
+                Materials([2, 0, 7, 0, 0]),
+            ],
+        };
+        assert_eq!(largest_number_of_geodes(&blueprint, 24), 9);
+        let mut costly = blueprint;
+        costly.costs[4] = Materials([2, 0, 7, 1, 0]);
+        assert_eq!(largest_number_of_geodes(&costly, 24), 8);
+    }
+
+    #[test]
+    fn malformed_blueprint() {
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. \
+                     Each obsidian robot costs 3 ore and 14 clay. \
+                     Each geode robot costs 2 ore and 7 obsidian.\n\
+                     Blueprint 2: Each ore robot costs 2 clay.";
+        assert_eq!(
+            parse(input).unwrap_err().to_string(),
+            "day 19, line 2, column 37: expected 'ore.', found 'clay.'"
+        );
     }
 
     #[test_case("../testinput/day19.txt", 33; "on test input")]
-    #[test_case("../input/day19.txt", 0; "on real input")]
+    #[test_case("../input/day19.txt", 978; "on real input")]
     fn part1(filename: &str, expected: u32) {
-        assert_eq!(impl1(filename), expected);
+        assert_eq!(impl1(filename).unwrap(), expected);
+    }
+
+    #[test_case("../testinput/day19.txt", 56 * 62; "on test input")]
+    #[test_case("../input/day19.txt", 15939; "on real input")]
+    fn part2(filename: &str, expected: u32) {
+        assert_eq!(impl2(filename).unwrap(), expected);
     }
 }
@@ -1,29 +1,60 @@
-use std::fs::read_to_string;
+use crate::{input::lines, Error, ParseError, Solution};
 
-fn score_strategy(filename: &str, score: &[&str]) -> u64 {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+const SELECTED: [&str; 9] = [
+    "B X", "C Y", "A Z", "A X", "B Y", "C Z", "C X", "A Y", "B Z",
+];
+
+const END: [&str; 9] = [
+    "B X", "C X", "A X", "A Y", "B Y", "C Y", "C Z", "A Z", "B Z",
+];
+
+fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    lines(2, input)
+        .map(|line| {
+            if SELECTED.contains(&line.text) {
+                Ok(line.text.to_string())
+            } else {
+                Err(line.error(line.text, "expected a round like 'A Y'"))
+            }
+        })
+        .collect()
+}
+
+fn score_strategy(rounds: &[String], score: &[&str]) -> u64 {
+    rounds
+        .iter()
         .map(|s| score.iter().position(|g| *g == s).unwrap() as u64 + 1)
         .sum()
 }
 
-pub fn score_selected(filename: &str) -> u64 {
-    score_strategy(
-        filename,
-        &[
-            "B X", "C Y", "A Z", "A X", "B Y", "C Z", "C X", "A Y", "B Z",
-        ],
-    )
+pub fn score_selected(filename: &str) -> Result<u64, Error> {
+    Ok(score_strategy(&Day2::parse_file(filename)?, &SELECTED))
 }
 
-pub fn score_end(filename: &str) -> u64 {
-    score_strategy(
-        filename,
-        &[
-            "B X", "C X", "A X", "A Y", "B Y", "C Y", "C Z", "A Z", "B Z",
-        ],
-    )
+pub fn score_end(filename: &str) -> Result<u64, Error> {
+    Ok(score_strategy(&Day2::parse_file(filename)?, &END))
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<String>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(rounds: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(score_strategy(rounds, &SELECTED)))
+    }
+
+    fn part2(rounds: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(score_strategy(rounds, &END)))
+    }
 }
 
 #[cfg(test)]
@@ -34,11 +65,11 @@ mod tests {
     #[test_case("../testinput/day2.txt", 15; "on test input")]
     #[test_case("../input/day2.txt", 12586; "on real input")]
     fn part1(filename: &str, expected: u64) {
-        assert_eq!(score_selected(filename), expected);
+        assert_eq!(score_selected(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day2.txt", 12; "on test input")]
     #[test_case("../input/day2.txt", 13193; "on real input")]
     fn part2(filename: &str, expected: u64) {
-        assert_eq!(score_end(filename), expected);
+        assert_eq!(score_end(filename).unwrap(), expected);
     }
 }
@@ -1,126 +1,186 @@
-use fxhash::FxHashSet as HashSet;
-use itertools::Itertools;
-use std::fs::read_to_string;
-
-pub fn ximpl1(filename: &str) -> i32 {
-    let numbers = read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.parse::<i32>().unwrap())
-        .collect_vec();
-    let mut seq = numbers.clone();
-    let nlen = i32::try_from(numbers.len()).unwrap();
-    for n in &numbers {
-        let i = seq.iter().position(|x| *x == *n).unwrap();
-        println!("n = {}, i = {}", *n, i);
-        seq.remove(i);
-        let s = i32::try_from(i).unwrap();
-        let q = (s + *n + nlen) % nlen;
-        let u = if *n < 0 { (nlen + q - 1) % nlen } else { q };
-        let index = usize::try_from(u).unwrap();
-        println!("q = {}, index = {}", q, index);
-        seq.insert(index, *n);
-        println!(
-            "{} moves between {} and {}:\n{:?}\n",
-            *n % nlen,
-            seq[(numbers.len() + index - 1) % numbers.len()],
-            seq[(index + 1) % numbers.len()],
-            seq
-        );
-    }
-    let zero = seq.iter().position(|n| *n == 0).unwrap();
-    (1..=3)
-        .map(|t| seq[(t * 1000 + zero) % numbers.len()])
-        .sum()
-}
+use crate::{circle::Circle, input::lines, Error, ParseError, Solution};
+
+/// The key the numbers are multiplied with in part 2.
+pub const DECRYPTION_KEY: i64 = 811_589_153;
 
-#[derive(Debug)]
-struct Number {
-    n: i32,
-    i: usize,
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    let numbers = lines(20, input)
+        .map(|line| line.number(line.text))
+        .collect::<Result<Vec<i64>, _>>()?;
+    if !numbers.contains(&0) {
+        return Err(ParseError::input(20, "no 0 in the file"));
+    }
+    Ok(numbers)
 }
 
-fn check_indices(vec: &[Number]) {
-    vec.iter()
-        .sorted_by(|a, b| a.i.cmp(&b.i))
-        .enumerate()
-        .for_each(|(next, number)| assert_eq!(number.i, next));
+/// Mixes `numbers` `rounds` times, moving each number in its original order
+/// as many positions forward or backward as its value.
+///
+/// The numbers are told apart by their original positions, so that
+/// duplicate values are still moved one at a time.
+pub fn mix(numbers: &[i64], rounds: usize) -> Vec<i64> {
+    mix_ids(numbers, rounds)
+        .into_iter()
+        .map(|id| numbers[id])
+        .collect()
 }
 
-pub fn impl1(filename: &str) -> i32 {
-    let numbers = read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.parse::<i32>().unwrap())
-        .collect_vec();
-    let mut mutnumbers = numbers
-        .iter()
-        .enumerate()
-        .map(|(i, n)| Number { n: *n, i })
-        .collect_vec();
-    let nlen_usize = numbers.len();
-    let nlen_i32 = i32::try_from(nlen_usize).unwrap();
-    for i in 0..nlen_usize {
-        let index_usize = mutnumbers[i].i;
-        let index_i32 = i32::try_from(index_usize).unwrap();
-        let n = mutnumbers[i].n;
-        let new_index_i32 = if index_i32 + n > nlen_i32 {
-            let mut x = index_i32 + n + 1;
-            while x >= nlen_i32 {
-                x -= nlen_i32;
-            }
-            x
-        } else if index_i32 + n < 0 {
-            let mut x = index_i32 + n - 1;
-            while x < 0 {
-                x += nlen_i32;
-            }
-            x
-        } else {
-            index_i32 + n
-        };
-
-        let new_index_usize = usize::try_from(new_index_i32).unwrap();
-        // println!(
-        //     "i: {}, n: {:?}, new_index: {}",
-        //     i, mutnumbers[i], new_index_usize
-        // );
-        for number in &mut mutnumbers {
-            if new_index_usize > index_usize
-                && number.i > index_usize
-                && number.i <= new_index_usize
-            {
-                number.i -= 1;
-            } else if number.i < index_usize && number.i >= new_index_usize {
-                number.i += 1;
-            }
+/// Like [`mix`], with the number of positions to move each element by
+/// rather than its value, giving the mixed order of the original positions.
+fn mix_ids(steps: &[i64], rounds: usize) -> Vec<usize> {
+    let mut circle = Circle::new((0..steps.len()).collect());
+    // Moving past all the other elements leaves the order as it was, and
+    // keeps the shifts within `i64`.
+    let others = i64::try_from(steps.len().max(2) - 1).unwrap();
+    for _ in 0..rounds {
+        for (id, &step) in steps.iter().enumerate() {
+            circle.shift(id, step.rem_euclid(others));
         }
-        mutnumbers[i].i = new_index_usize;
-        // println!("{:?}\n", mutnumbers);
-        check_indices(&mutnumbers);
     }
-    mutnumbers
-        .iter()
-        .sorted_by(|a, b| a.i.cmp(&b.i))
-        .for_each(|n| println!("{:04}: {}", n.i, n.n));
+    circle.iter().copied().collect()
+}
 
-    let zero = mutnumbers.iter().find(|n| n.n == 0).unwrap().i;
-    let indices: HashSet<usize> = (1..=3).map(|t| (t * 1000 + zero) % numbers.len()).collect();
-    mutnumbers
+/// The sum of the numbers 1000, 2000 and 3000 after the 0 once the numbers
+/// times `key` are mixed `rounds` times.
+///
+/// The products may not fit in an `i64`, so the numbers are moved by their
+/// products modulo the number of other elements, and only the sum uses the
+/// products themselves.
+pub fn grove_coordinates(numbers: &[i64], key: i64, rounds: usize) -> i128 {
+    let others = i128::try_from(numbers.len().max(2) - 1).unwrap();
+    let steps: Vec<i64> = numbers
         .iter()
-        .filter(|n| indices.contains(&n.i))
-        .map(|n| n.n)
+        .map(|&number| i64::try_from(i128::from(number) * i128::from(key) % others).unwrap())
+        .collect();
+    let mixed = mix_ids(&steps, rounds);
+    let zero = mixed.iter().position(|&id| numbers[id] == 0).unwrap();
+    [1000, 2000, 3000]
+        .iter()
+        .map(|offset| i128::from(numbers[mixed[(zero + offset) % mixed.len()]]) * i128::from(key))
         .sum()
 }
 
+pub fn impl1(filename: &str) -> Result<i128, Error> {
+    Ok(grove_coordinates(&Day20::parse_file(filename)?, 1, 1))
+}
+
+pub fn impl2(filename: &str) -> Result<i128, Error> {
+    Ok(grove_coordinates(
+        &Day20::parse_file(filename)?,
+        DECRYPTION_KEY,
+        10,
+    ))
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    type Input = Vec<i64>;
+    type Answer1 = i128;
+    type Answer2 = i128;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<Option<i128>, String> {
+        Ok(Some(grove_coordinates(numbers, 1, 1)))
+    }
+
+    fn part2(numbers: &Self::Input) -> Result<Option<i128>, String> {
+        Ok(Some(grove_coordinates(numbers, DECRYPTION_KEY, 10)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::impl1;
+    use super::{grove_coordinates, impl1, impl2, mix, parse, Day20, DECRYPTION_KEY};
+    use crate::Solution;
     use test_case::test_case;
 
+    /// The same circle starting from the 0, to compare mixes regardless of
+    /// where the circle is cut.
+    fn from_zero(numbers: &[i64]) -> Vec<i64> {
+        let zero = numbers.iter().position(|&number| number == 0).unwrap();
+        numbers[zero..]
+            .iter()
+            .chain(&numbers[..zero])
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn mixing() {
+        let numbers = Day20::parse_file("../testinput/day20.txt").unwrap();
+        assert_eq!(from_zero(&mix(&numbers, 1)), [0, 3, -2, 1, 2, -3, 4]);
+        let numbers: Vec<i64> = numbers.iter().map(|n| n * DECRYPTION_KEY).collect();
+        assert_eq!(
+            from_zero(&mix(&numbers, 10)),
+            [
+                0,
+                -2_434_767_459,
+                1_623_178_306,
+                3_246_356_612,
+                -1_623_178_306,
+                2_434_767_459,
+                811_589_153
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        // Each 1 moves once, past the other 1, and moving three positions
+        // in a circle of four takes a number back where it was.
+        assert_eq!(from_zero(&mix(&[1, 1, 0, 5], 1)), [0, 1, 1, 5]);
+        assert_eq!(from_zero(&mix(&[0, 3, 3, 3], 1)), [0, 3, 3, 3]);
+    }
+
+    #[test]
+    fn many_numbers() {
+        let numbers: Vec<i64> = (0..100_000).map(|i| (i * 7919) % 20_001 - 10_000).collect();
+        let mut mixed = mix(&numbers, 1);
+        mixed.sort_unstable();
+        let mut sorted = numbers;
+        sorted.sort_unstable();
+        assert_eq!(mixed, sorted);
+    }
+
+    #[test]
+    fn large_numbers() {
+        // Moving i64::MAX is moving past the other two once, and i64::MIN
+        // is a whole number of times around.
+        assert_eq!(
+            from_zero(&mix(&[i64::MAX, 0, i64::MIN], 1)),
+            [0, i64::MAX, i64::MIN]
+        );
+        // With three numbers the coordinates are all of them, and times the
+        // key they do not fit in an i64.
+        assert_eq!(
+            grove_coordinates(&[1, 0, 20_000_000_000], DECRYPTION_KEY, 10),
+            20_000_000_001 * i128::from(DECRYPTION_KEY)
+        );
+    }
+
+    #[test]
+    fn no_zero() {
+        assert_eq!(
+            parse("1\n2\n").unwrap_err().to_string(),
+            "day 20: no 0 in the file"
+        );
+    }
+
     #[test_case("../testinput/day20.txt", 3; "on test input")]
-    #[test_case("../input/day20.txt", 0; "on real input")]
-    fn part1(filename: &str, expected: i32) {
-        assert_eq!(impl1(filename), expected);
+    #[test_case("../input/day20.txt", 7395; "on real input")]
+    fn part1(filename: &str, expected: i128) {
+        assert_eq!(impl1(filename).unwrap(), expected);
+    }
+
+    #[test_case("../testinput/day20.txt", 1_623_178_306; "on test input")]
+    #[test_case("../input/day20.txt", 1_640_221_678_213; "on real input")]
+    fn part2(filename: &str, expected: i128) {
+        assert_eq!(impl2(filename).unwrap(), expected);
     }
 }
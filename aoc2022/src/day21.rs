@@ -1,5 +1,14 @@
-use fxhash::FxHashMap as HashMap;
-use std::fs::read_to_string;
+use crate::{
+    input::{lines, Line},
+    Error, ParseError, Solution,
+};
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use num_rational::{BigRational, Ratio};
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, ToPrimitive, Zero};
+use std::fmt;
+
+/// Exact numbers for solving equations, so that division never truncates.
+pub type Rational = Ratio<i128>;
 
 #[derive(Debug, Clone)]
 enum Operator {
@@ -9,6 +18,24 @@ enum Operator {
     Mul,
 }
 
+impl Operator {
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Div => '/',
+            Operator::Mul => '*',
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Div | Operator::Mul => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Operation {
     operator: Operator,
@@ -23,8 +50,9 @@ enum Job {
 }
 
 #[derive(Debug, Clone)]
-struct Node {
-    node: XNode,
+pub struct Node {
+    name: String,
+    kind: XNode,
     is_flagged: bool,
 }
 
@@ -41,26 +69,28 @@ enum XNode {
     Outer(i64),
 }
 
-fn parse_file(filename: &str) -> Node {
+fn parse(input: &str) -> Result<Node, ParseError> {
     let mut map = HashMap::default();
-    for line in read_to_string(filename).unwrap().lines() {
-        let mut split = line.split(": ");
-        let name = split.next().unwrap();
-        let jobstr = split.next().unwrap();
-        if jobstr.chars().next().unwrap().is_numeric() {
-            map.insert(name.to_string(), Job::Number(jobstr.parse().unwrap()));
+    let mut references = Vec::new();
+    let mut waits = HashMap::default();
+    for line in lines(21, input) {
+        let [name, jobstr] = line.split(line.text, ": ")?;
+        if jobstr.starts_with(|c: char| c.is_ascii_digit()) {
+            map.insert(name.to_string(), Job::Number(line.number(jobstr)?));
         } else {
-            let mut osplit = jobstr.split(' ');
-            let a = osplit.next().unwrap();
-            let op = match osplit.next().unwrap() {
+            let [a, op, b] = line.split(jobstr, " ")?;
+            let op = match op {
                 "+" => Operator::Add,
                 "-" => Operator::Sub,
                 "*" => Operator::Mul,
                 "/" => Operator::Div,
-                _ => panic!("parse error '{}'", line),
+                _ => return Err(line.error(op, "expected one of '+', '-', '*' or '/'")),
             };
-            let b = osplit.next().unwrap();
-
+            if name == "humn" {
+                return Err(line.error(jobstr, "expected humn to yell a number"));
+            }
+            references.extend([(line, a), (line, b)]);
+            waits.insert(name, (line, [a, b]));
             map.insert(
                 name.to_string(),
                 Job::Operation(Operation {
@@ -71,23 +101,56 @@ fn parse_file(filename: &str) -> Node {
             );
         }
     }
-    tree("root", &map)
+    if let Some((line, name)) = references.iter().find(|(_, name)| !map.contains_key(*name)) {
+        return Err(line.error(name, "no such monkey"));
+    }
+    for name in ["root", "humn"] {
+        if !map.contains_key(name) {
+            return Err(ParseError::input(21, format!("no monkey named {name}")));
+        }
+    }
+    find_cycle("root", &waits, &mut Vec::new(), &mut HashSet::default())?;
+    Ok(tree("root", &map))
+}
+
+/// Fails at the first monkey found to wait, through other monkeys, for its
+/// own number, which would otherwise make [`tree`] recurse forever.
+fn find_cycle<'a>(
+    monkey: &'a str,
+    waits: &HashMap<&'a str, (Line<'a>, [&'a str; 2])>,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Result<(), ParseError> {
+    if done.contains(monkey) {
+        return Ok(());
+    }
+    if let Some((line, operands)) = waits.get(monkey) {
+        path.push(monkey);
+        for &operand in operands {
+            if path.contains(&operand) {
+                return Err(line.error(operand, "monkey waits for its own number"));
+            }
+            find_cycle(operand, waits, path, done)?;
+        }
+        path.pop();
+    }
+    done.insert(monkey);
+    Ok(())
 }
 
 fn tree(monkey: &str, monkeys: &HashMap<String, Job>) -> Node {
-    let o = monkeys.get(monkey).unwrap();
-    if monkey == "humn" {
+    let o = &monkeys[monkey];
+    if let ("humn", Job::Number(x)) = (monkey, o) {
         Node {
-            node: XNode::Outer(match o {
-                Job::Number(x) => *x,
-                Job::Operation(_) => panic!("humn not number"),
-            }),
+            name: monkey.to_string(),
+            kind: XNode::Outer(*x),
             is_flagged: true,
         }
     } else {
         match o {
             Job::Number(n) => Node {
-                node: XNode::Outer(*n),
+                name: monkey.to_string(),
+                kind: XNode::Outer(*n),
                 is_flagged: false,
             },
             Job::Operation(op) => {
@@ -95,7 +158,8 @@ fn tree(monkey: &str, monkeys: &HashMap<String, Job>) -> Node {
                 let right = tree(&op.right, monkeys);
                 let is_flagged = left.is_flagged | right.is_flagged;
                 Node {
-                    node: XNode::Inner(Box::new(InnerNode {
+                    name: monkey.to_string(),
+                    kind: XNode::Inner(Box::new(InnerNode {
                         operator: op.operator.clone(),
                         left: Box::new(left),
                         right: Box::new(right),
@@ -107,96 +171,571 @@ fn tree(monkey: &str, monkeys: &HashMap<String, Job>) -> Node {
     }
 }
 
-fn sum_tree(root: &Node) -> i64 {
-    match &root.node {
-        XNode::Outer(n) => *n,
+/// The number the root yells, with `i64` arithmetic and truncating
+/// division, or `None` on overflow or division by zero. This is the fast
+/// path for well behaved input; see [`evaluate_exact`] for checking that it
+/// is.
+fn sum_tree(root: &Node) -> Option<i64> {
+    match &root.kind {
+        XNode::Outer(n) => Some(*n),
         XNode::Inner(boxed) => {
             let inner = &**boxed;
-            let left = sum_tree(&inner.left);
-            let right = sum_tree(&inner.right);
+            let left = sum_tree(&inner.left)?;
+            let right = sum_tree(&inner.right)?;
+            match inner.operator {
+                Operator::Add => left.checked_add(right),
+                Operator::Sub => left.checked_sub(right),
+                Operator::Mul => left.checked_mul(right),
+                Operator::Div => left.checked_div(right),
+            }
+        }
+    }
+}
+
+/// The number the root yells, falling back to exact arithmetic where the
+/// fast path overflows or divides by zero.
+fn yell(root: &Node) -> Result<i64, String> {
+    if let Some(n) = sum_tree(root) {
+        return Ok(n);
+    }
+    let exact = evaluate_exact(root).map_err(|err| err.to_string())?;
+    match exact.value.to_i64() {
+        Some(n) if exact.value.is_integer() => Ok(n),
+        _ => Err(format!(
+            "{} yells {}, which is not an i64",
+            root.name, exact.value
+        )),
+    }
+}
+
+/// Infix with only the parentheses needed, and the flagged monkey by name.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = match &self.kind {
+            XNode::Outer(_) if self.is_flagged => return write!(f, "{}", self.name),
+            XNode::Outer(n) => return write!(f, "{n}"),
+            XNode::Inner(inner) => inner,
+        };
+        let precedence = inner.operator.precedence();
+        if inner.left.precedence() < precedence {
+            write!(f, "({})", inner.left)?;
+        } else {
+            write!(f, "{}", inner.left)?;
+        }
+        write!(f, " {} ", inner.operator.symbol())?;
+        // Subtraction and division do not associate, nor does truncating
+        // division with multiplication, and a negative number would read as
+        // another operator.
+        let right = inner.right.precedence();
+        let negative = matches!(inner.right.kind, XNode::Outer(n) if n < 0);
+        let divides = match &inner.right.kind {
+            XNode::Inner(right) => matches!(right.operator, Operator::Div),
+            XNode::Outer(_) => false,
+        };
+        if right < precedence
+            || right == precedence && matches!(inner.operator, Operator::Sub | Operator::Div)
+            || divides && matches!(inner.operator, Operator::Mul)
+            || negative
+        {
+            write!(f, "({})", inner.right)
+        } else {
+            write!(f, "{}", inner.right)
+        }
+    }
+}
+
+/// The exact number a monkey yells, and where `i64` arithmetic would have
+/// given another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub value: BigRational,
+    /// Paths from the root like `root/pppw/cczh` to the monkeys whose
+    /// division has a remainder.
+    pub inexact: Vec<String>,
+    /// Paths to the monkeys whose number does not fit in an `i64`.
+    pub overflows: Vec<String>,
+}
+
+impl Evaluation {
+    /// The value, if the fast path gets it right.
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.inexact.is_empty() && self.overflows.is_empty() {
+            self.value.to_i64()
+        } else {
+            None
+        }
+    }
+}
+
+/// A monkey divides by zero, at the end of the path from the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivisionByZero(pub String);
+
+impl fmt::Display for DivisionByZero {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "division by zero at {}", self.0)
+    }
+}
+
+impl std::error::Error for DivisionByZero {}
+
+/// Evaluates the tree with arbitrary precision rationals, noting every
+/// division and number that the `i64` fast path would get wrong.
+pub fn evaluate_exact(root: &Node) -> Result<Evaluation, DivisionByZero> {
+    let mut evaluation = Evaluation {
+        value: BigRational::zero(),
+        inexact: Vec::new(),
+        overflows: Vec::new(),
+    };
+    evaluation.value = exact(root, &root.name, &mut evaluation)?;
+    Ok(evaluation)
+}
+
+fn exact(
+    node: &Node,
+    path: &str,
+    evaluation: &mut Evaluation,
+) -> Result<BigRational, DivisionByZero> {
+    let value = match &node.kind {
+        XNode::Outer(n) => BigRational::from_integer((*n).into()),
+        XNode::Inner(inner) => {
+            let left = exact(
+                &inner.left,
+                &format!("{path}/{}", inner.left.name),
+                evaluation,
+            )?;
+            let right = exact(
+                &inner.right,
+                &format!("{path}/{}", inner.right.name),
+                evaluation,
+            )?;
             match inner.operator {
                 Operator::Add => left + right,
                 Operator::Sub => left - right,
                 Operator::Mul => left * right,
-                Operator::Div => left / right,
+                Operator::Div => {
+                    if right.is_zero() {
+                        return Err(DivisionByZero(path.to_string()));
+                    }
+                    let quotient = left / right;
+                    if !quotient.is_integer() {
+                        evaluation.inexact.push(path.to_string());
+                    }
+                    quotient
+                }
             }
         }
+    };
+    if value.to_integer().to_i64().is_none() {
+        evaluation.overflows.push(path.to_string());
+    }
+    Ok(value)
+}
+
+/// Why an equation could not be solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The unknown monkey is not part of the equation.
+    NoSuchMonkey(String),
+    /// The monkey at the root yells a number rather than comparing two.
+    NotAnEquation(String),
+    /// The unknown is multiplied by itself or divides at the monkey.
+    Nonlinear(String),
+    DivisionByZero(String),
+    Overflow(String),
+    NoSolution,
+    EverySolution,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::NoSuchMonkey(name) => write!(f, "no monkey named {name} in the equation"),
+            SolveError::NotAnEquation(name) => write!(f, "{name} yells a number, not an equation"),
+            SolveError::Nonlinear(name) => write!(f, "the equation is not linear at {name}"),
+            SolveError::DivisionByZero(name) => write!(f, "division by zero at {name}"),
+            SolveError::Overflow(name) => write!(f, "overflow at {name}"),
+            SolveError::NoSolution => write!(f, "no value solves the equation"),
+            SolveError::EverySolution => write!(f, "every value solves the equation"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// The value `a * x + b` of a monkey in terms of the unknown `x`.
+#[derive(Debug, Clone, Copy)]
+struct Linear {
+    a: Rational,
+    b: Rational,
+}
+
+impl Linear {
+    fn constant(b: Rational) -> Self {
+        Linear {
+            a: Rational::zero(),
+            b,
+        }
+    }
+
+    fn has_unknown(&self) -> bool {
+        !self.a.is_zero()
     }
 }
 
-fn walk_tree(root: &Node, balance: i64) -> i64 {
-    match &root.node {
-        XNode::Outer(_) => {
-            if root.is_flagged {
-                return balance;
+impl Node {
+    /// How tightly the expression binds when printed, numbers and names
+    /// being the tightest.
+    fn precedence(&self) -> u8 {
+        match &self.kind {
+            XNode::Outer(_) => 3,
+            XNode::Inner(inner) => inner.operator.precedence(),
+        }
+    }
+
+    /// The same tree with every subtree without the flagged monkey folded
+    /// into the number it yells. Subtrees that the `i64` fast path would get
+    /// wrong, see [`evaluate_exact`], are only folded below that point.
+    #[must_use]
+    pub fn simplify(&self) -> Node {
+        let XNode::Inner(inner) = &self.kind else {
+            return self.clone();
+        };
+        if !self.is_flagged {
+            if let Some(n) = evaluate_exact(self).ok().and_then(|exact| exact.to_i64()) {
+                return Node {
+                    name: self.name.clone(),
+                    kind: XNode::Outer(n),
+                    is_flagged: false,
+                };
             }
-            panic!("malformed tree");
         }
-        XNode::Inner(boxed) => {
-            let inner = &**boxed;
-            let left = &*inner.left;
-            let right = &*inner.right;
-            if left.is_flagged {
-                let right_sum = sum_tree(right);
-                walk_tree(
-                    left,
-                    match inner.operator {
-                        Operator::Add => balance - right_sum,
-                        Operator::Sub => balance + right_sum,
-                        Operator::Mul => balance / right_sum,
-                        Operator::Div => balance * right_sum,
-                    },
-                )
-            } else {
-                let left_sum = sum_tree(left);
-                walk_tree(
-                    right,
-                    match inner.operator {
-                        Operator::Add => balance - left_sum,
-                        Operator::Sub => left_sum - balance,
-                        Operator::Mul => balance / left_sum,
-                        Operator::Div => left_sum / balance,
-                    },
-                )
+        Node {
+            name: self.name.clone(),
+            kind: XNode::Inner(Box::new(InnerNode {
+                operator: inner.operator.clone(),
+                left: Box::new(inner.left.simplify()),
+                right: Box::new(inner.right.simplify()),
+            })),
+            is_flagged: self.is_flagged,
+        }
+    }
+
+    fn contains(&self, monkey: &str) -> bool {
+        self.name == monkey
+            || match &self.kind {
+                XNode::Outer(_) => false,
+                XNode::Inner(inner) => inner.left.contains(monkey) || inner.right.contains(monkey),
+            }
+    }
+
+    /// The value of this monkey as a linear function of what `unknown`
+    /// yells.
+    fn linear(&self, unknown: &str) -> Result<Linear, SolveError> {
+        if self.name == unknown {
+            return Ok(Linear {
+                a: Rational::from(1),
+                b: Rational::zero(),
+            });
+        }
+        let inner = match &self.kind {
+            XNode::Outer(n) => return Ok(Linear::constant(Rational::from(i128::from(*n)))),
+            XNode::Inner(inner) => inner,
+        };
+        let left = inner.left.linear(unknown)?;
+        let right = inner.right.linear(unknown)?;
+        let overflow = || SolveError::Overflow(self.name.clone());
+        match inner.operator {
+            Operator::Add => Ok(Linear {
+                a: left.a.checked_add(&right.a).ok_or_else(overflow)?,
+                b: left.b.checked_add(&right.b).ok_or_else(overflow)?,
+            }),
+            Operator::Sub => Ok(Linear {
+                a: left.a.checked_sub(&right.a).ok_or_else(overflow)?,
+                b: left.b.checked_sub(&right.b).ok_or_else(overflow)?,
+            }),
+            Operator::Mul => {
+                let (linear, factor) = match (left.has_unknown(), right.has_unknown()) {
+                    (true, true) => return Err(SolveError::Nonlinear(self.name.clone())),
+                    (true, false) => (left, right.b),
+                    (false, _) => (right, left.b),
+                };
+                Ok(Linear {
+                    a: linear.a.checked_mul(&factor).ok_or_else(overflow)?,
+                    b: linear.b.checked_mul(&factor).ok_or_else(overflow)?,
+                })
+            }
+            Operator::Div => {
+                if right.has_unknown() {
+                    return Err(SolveError::Nonlinear(self.name.clone()));
+                }
+                if right.b.is_zero() {
+                    return Err(SolveError::DivisionByZero(self.name.clone()));
+                }
+                Ok(Linear {
+                    a: left.a.checked_div(&right.b).ok_or_else(overflow)?,
+                    b: left.b.checked_div(&right.b).ok_or_else(overflow)?,
+                })
             }
         }
     }
 }
 
-pub fn monkey_yell(filename: &str) -> i64 {
-    let root = parse_file(filename);
-    sum_tree(&root)
+/// The equation of the root as `a * unknown + b = c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    /// The monkey comparing the two sides.
+    pub root: String,
+    pub unknown: String,
+    pub a: Rational,
+    pub b: Rational,
+    pub c: Rational,
+}
+
+impl Equation {
+    pub fn solution(&self) -> Result<Rational, SolveError> {
+        let overflow = || SolveError::Overflow(self.root.clone());
+        let rest = self.c.checked_sub(&self.b).ok_or_else(overflow)?;
+        match (self.a.is_zero(), rest.is_zero()) {
+            (true, true) => Err(SolveError::EverySolution),
+            (true, false) => Err(SolveError::NoSolution),
+            _ => rest.checked_div(&self.a).ok_or_else(overflow),
+        }
+    }
 }
 
-pub fn equality_test(filename: &str) -> i64 {
-    let root = parse_file(filename);
-    if let XNode::Inner(boxed) = root.node {
-        let inner = *boxed;
-        let left = *inner.left;
-        let right = *inner.right;
-        if left.is_flagged {
-            walk_tree(&left, sum_tree(&right))
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.a.is_integer() {
+            write!(f, "{}*{}", self.a, self.unknown)?;
         } else {
-            walk_tree(&right, sum_tree(&left))
+            write!(f, "({})*{}", self.a, self.unknown)?;
         }
-    } else {
-        0
+        if self.b < Rational::zero() {
+            write!(f, " - {}", -self.b)?;
+        } else if !self.b.is_zero() {
+            write!(f, " + {}", self.b)?;
+        }
+        write!(f, " = {}", self.c)
+    }
+}
+
+/// The root's comparison as a linear equation in what `unknown` yells, with
+/// the unknown on the left. The unknown may be any monkey below the root,
+/// on either side and in several places, as long as the equation is linear.
+pub fn equation(root: &Node, unknown: &str) -> Result<Equation, SolveError> {
+    let XNode::Inner(inner) = &root.kind else {
+        return Err(SolveError::NotAnEquation(root.name.clone()));
+    };
+    if !inner.left.contains(unknown) && !inner.right.contains(unknown) {
+        return Err(SolveError::NoSuchMonkey(unknown.to_string()));
+    }
+    let mut left = inner.left.linear(unknown)?;
+    let mut right = inner.right.linear(unknown)?;
+    if !left.has_unknown() {
+        (left, right) = (right, left);
+    }
+    // a * x + b = c * x + d, so (a - c) * x + b = d.
+    Ok(Equation {
+        root: root.name.clone(),
+        unknown: unknown.to_string(),
+        a: left
+            .a
+            .checked_sub(&right.a)
+            .ok_or_else(|| SolveError::Overflow(root.name.clone()))?,
+        b: left.b,
+        c: right.b,
+    })
+}
+
+/// What `unknown` has to yell for the two monkeys the root listens to to
+/// yell the same number.
+pub fn solve(root: &Node, unknown: &str) -> Result<Rational, SolveError> {
+    equation(root, unknown)?.solution()
+}
+
+/// The root's comparison in infix, like `(4 + 2 * (humn - 3)) / 4 = 150`.
+pub fn equality(root: &Node) -> Result<String, SolveError> {
+    match &root.kind {
+        XNode::Inner(inner) => Ok(format!("{} = {}", inner.left, inner.right)),
+        XNode::Outer(_) => Err(SolveError::NotAnEquation(root.name.clone())),
+    }
+}
+
+/// What the root yells, or why it is not an `i64` once the file is parsed.
+pub fn monkey_yell(filename: &str) -> Result<Result<i64, String>, Error> {
+    Ok(yell(&Day21::parse_file(filename)?))
+}
+
+/// What humn yells, or why no number will do once the file is parsed.
+pub fn equality_test(filename: &str) -> Result<Result<Rational, SolveError>, Error> {
+    Ok(solve(&Day21::parse_file(filename)?, "humn"))
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    type Input = Node;
+    type Answer1 = i64;
+    type Answer2 = Rational;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(root: &Self::Input) -> Result<Option<i64>, String> {
+        yell(root).map(Some)
+    }
+
+    fn part2(root: &Self::Input) -> Result<Option<Rational>, String> {
+        solve(root, "humn").map(Some).map_err(|err| err.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{equality_test, monkey_yell};
+    use super::{
+        equality, equality_test, equation, evaluate_exact, monkey_yell, parse, solve, yell, Day21,
+        Rational, SolveError,
+    };
+    use crate::Solution;
+    use num_rational::BigRational;
     use test_case::test_case;
 
+    fn solve_for(input: &str, unknown: &str) -> Result<Rational, SolveError> {
+        solve(&parse(input).unwrap(), unknown)
+    }
+
+    #[test_case("root: a + b\na: b + humn\nb: a * a\nhumn: 1\n", "day 21, line 3, column 4: monkey waits for its own number, found 'a'"; "cycle")]
+    #[test_case("root: root + humn\nhumn: 1\n", "day 21, line 1, column 7: monkey waits for its own number, found 'root'"; "root")]
+    fn invalid(input: &str, expected: &str) {
+        assert_eq!(parse(input).unwrap_err().to_string(), expected);
+    }
+
+    #[test]
+    fn unknowns() {
+        let input = "root: a + b\na: humn + humn\nb: 10\nhumn: 1\n";
+        assert_eq!(solve_for(input, "humn"), Ok(Rational::from(5)));
+        // Any monkey, on either side.
+        assert_eq!(solve_for(input, "b"), Ok(Rational::from(2)));
+        assert_eq!(solve_for(input, "a"), Ok(Rational::from(10)));
+        let input = "root: b + a\na: humn * c\nb: 2\nc: 3\nhumn: 1\n";
+        assert_eq!(solve_for(input, "humn"), Ok(Rational::new(2, 3)));
+    }
+
+    #[test_case("a: humn * humn\nb: 4", SolveError::Nonlinear("a".to_string()); "nonlinear")]
+    #[test_case("a: b / humn\nb: 4", SolveError::Nonlinear("a".to_string()); "unknown divisor")]
+    #[test_case("a: humn / c\nb: 4\nc: 0", SolveError::DivisionByZero("a".to_string()); "by zero")]
+    #[test_case("a: humn - humn\nb: 4", SolveError::NoSolution; "no solution")]
+    #[test_case("a: humn - humn\nb: 0", SolveError::EverySolution; "every solution")]
+    fn unsolvable(monkeys: &str, expected: SolveError) {
+        let input = format!("root: a + b\nhumn: 1\n{monkeys}\n");
+        assert_eq!(solve_for(&input, "humn"), Err(expected));
+    }
+
+    #[test]
+    fn exact() {
+        let root = Day21::parse_file("../input/day21.txt").unwrap();
+        let evaluation = evaluate_exact(&root).unwrap();
+        assert_eq!(evaluation.to_i64(), Some(276_156_919_469_632));
+
+        let input = "root: a + b\na: c / d\nb: e * e\nc: 7\nd: 2\ne: 4000000000\nhumn: 1\n";
+        let evaluation = evaluate_exact(&parse(input).unwrap()).unwrap();
+        assert_eq!(
+            evaluation.value,
+            BigRational::new(32_000_000_000_000_000_007_u128.into(), 2.into())
+        );
+        assert_eq!(evaluation.inexact, ["root/a"]);
+        assert_eq!(evaluation.overflows, ["root/b", "root"]);
+        assert_eq!(evaluation.to_i64(), None);
+
+        let input = "root: a / b\na: 1\nb: c - c\nc: 2\nhumn: 1\n";
+        assert_eq!(
+            evaluate_exact(&parse(input).unwrap())
+                .unwrap_err()
+                .to_string(),
+            "division by zero at root"
+        );
+    }
+
+    #[test]
+    fn printing() {
+        let root = Day21::parse_file("../testinput/day21.txt").unwrap();
+        assert_eq!(
+            equality(&root).unwrap(),
+            "(4 + 2 * (humn - 3)) / 4 = (32 - 2) * 5"
+        );
+        assert_eq!(
+            equality(&root.simplify()).unwrap(),
+            "(4 + 2 * (humn - 3)) / 4 = 150"
+        );
+        assert_eq!(
+            equation(&root, "humn").unwrap().to_string(),
+            "(1/2)*humn - 1/2 = 150"
+        );
+        let input = "root: a + b\na: c - d\nb: e - f\nc: humn - g\nd: g * g\n\
+                     e: 3\nf: 5\ng: 2\nhumn: 1\n";
+        let root = parse(input).unwrap();
+        assert_eq!(equality(&root).unwrap(), "humn - 2 - 2 * 2 = 3 - 5");
+        let input = "root: b + a\na: c - d\nb: e / f\nc: humn * g\nd: e - f\n\
+                     e: 3\nf: 5\ng: 2\nhumn: 1\n";
+        let root = parse(input).unwrap().simplify();
+        assert_eq!(equality(&root).unwrap(), "3 / 5 = humn * 2 - (-2)");
+        assert_eq!(
+            equation(&root, "humn").unwrap().to_string(),
+            "2*humn + 2 = 3/5"
+        );
+        // 2 * 7 / 4 would be 3, not 2.
+        let input = "root: a + e\na: b * c\nb: 2\nc: d / f\nd: 7\nf: 4\ne: 1\nhumn: 1\n";
+        assert_eq!(equality(&parse(input).unwrap()).unwrap(), "2 * (7 / 4) = 1");
+    }
+
+    #[test_case("root: a / b\na: 1\nb: c - c\nc: 2", Err("division by zero at root"); "by zero")]
+    #[test_case("root: a * a\na: 5000000000", Err("root yells 25000000000000000000, which is not an i64"); "overflow")]
+    #[test_case("root: b / a\na: 5000000000\nb: c * a\nc: 3", Ok(3); "overflow on the way")]
+    fn fast_path(monkeys: &str, expected: Result<i64, &str>) {
+        let root = parse(&format!("{monkeys}\nhumn: 1\n")).unwrap();
+        assert_eq!(yell(&root), expected.map_err(ToString::to_string));
+    }
+
+    #[test]
+    fn unsolvable_part() {
+        let input = "root: a + b\na: humn * humn\nb: 4\nhumn: 1\n";
+        let parsed = crate::puzzle(21).unwrap().parse(input).unwrap();
+        assert_eq!(parsed.part1(), Ok(Some("5".to_string())));
+        assert_eq!(
+            parsed.part2(),
+            Err("the equation is not linear at a".to_string())
+        );
+    }
+
+    #[test]
+    fn not_an_equation() {
+        assert_eq!(
+            solve_for("root: a + b\na: 1\nb: 2\nhumn: 1\n", "humn"),
+            Err(SolveError::NoSuchMonkey("humn".to_string()))
+        );
+        assert_eq!(
+            solve_for("root: 1\nhumn: 1\n", "humn")
+                .unwrap_err()
+                .to_string(),
+            "root yells a number, not an equation"
+        );
+    }
+
     #[test_case("../testinput/day21.txt", 152; "on test input")]
     #[test_case("../input/day21.txt", 276_156_919_469_632; "on real input")]
     fn part1(filename: &str, expected: i64) {
-        assert_eq!(monkey_yell(filename), expected);
+        assert_eq!(monkey_yell(filename).unwrap(), Ok(expected));
     }
     #[test_case("../testinput/day21.txt", 301; "on test input")]
     #[test_case("../input/day21.txt", 3_441_198_826_073; "on real input")]
-    fn part2(filename: &str, expected: i64) {
-        assert_eq!(equality_test(filename), expected);
+    fn part2(filename: &str, expected: i128) {
+        assert_eq!(
+            equality_test(filename).unwrap(),
+            Ok(Rational::from(expected))
+        );
     }
 }
@@ -1,6 +1,6 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use fxhash::FxHashMap as HashMap;
 use itertools::Itertools;
-use std::fs::read_to_string;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Tile {
@@ -37,6 +37,11 @@ enum Facing {
 
 type Map = HashMap<Coord, Tile>;
 
+pub struct Notes {
+    map: Map,
+    path: Vec<Move>,
+}
+
 fn wrap0(state: &State, map: &Map) -> Option<State> {
     let (coord, tile) = match state.facing {
         Facing::Right => map
@@ -300,27 +305,36 @@ fn try_move(state: &State, map: &Map, mode: i32) -> Option<State> {
     }
 }
 
-pub fn final_password(filename: &str, mode: i32) -> i32 {
-    let binding = read_to_string(filename).unwrap();
-    let lines = binding.lines().collect_vec();
-    let mut split = lines.split(|line| line.is_empty());
+fn parse(input: &str) -> Result<Notes, ParseError> {
+    let mut lines = lines(22, input);
     let mut map: Map = HashMap::default();
-    for (row_usize, line) in split.next().unwrap().iter().enumerate() {
-        for (col_usize, c) in line.chars().enumerate() {
+    for (row_usize, line) in lines
+        .by_ref()
+        .take_while(|line| !line.text.is_empty())
+        .enumerate()
+    {
+        for (col_usize, c) in line.text.char_indices() {
             let row = i32::try_from(row_usize).unwrap();
             let col = i32::try_from(col_usize).unwrap();
             match c {
                 '.' => map.insert(Coord { row, col }, Tile::Open),
                 '#' => map.insert(Coord { row, col }, Tile::Wall),
-                _ => None,
+                ' ' => None,
+                _ => return Err(line.error_at(col_usize, "expected '.', '#' or ' '")),
             };
         }
     }
+    if map.is_empty() {
+        return Err(ParseError::input(22, "no map"));
+    }
+    let Some(line) = lines.next() else {
+        return Err(ParseError::input(22, "no path after the map"));
+    };
     let mut path = Vec::<Move>::new();
     let mut i: i32 = 0;
-    for c in split.next().unwrap().iter().next().unwrap().chars() {
+    for (pos, c) in line.text.char_indices() {
         match c {
-            c if c.is_numeric() => {
+            c if c.is_ascii_digit() => {
                 i *= 10;
                 i += i32::try_from(c.to_digit(10).unwrap()).unwrap();
             }
@@ -334,11 +348,17 @@ pub fn final_password(filename: &str, mode: i32) -> i32 {
                 i = 0;
                 path.push(Move::Right);
             }
-            _ => panic!("parse error"),
-        };
+            _ => return Err(line.error_at(pos, "expected a number, 'L' or 'R'")),
+        }
     }
     path.push(Move::Number(i));
+    Ok(Notes { map, path })
+}
 
+/// Final password, wrapping around the flat map in `mode` 0 or folding it
+/// into a cube laid out like the test input (1) or the real input (2).
+pub fn walk(notes: &Notes, mode: i32) -> i32 {
+    let map = &notes.map;
     let mut state = State {
         coord: map
             .keys()
@@ -355,7 +375,7 @@ pub fn final_password(filename: &str, mode: i32) -> i32 {
         facing: Facing::Right,
     };
 
-    for step in path {
+    for step in &notes.path {
         match step {
             Move::Right => {
                 state.facing = match state.facing {
@@ -374,8 +394,8 @@ pub fn final_password(filename: &str, mode: i32) -> i32 {
                 }
             }
             Move::Number(n) => {
-                for _ in 0..n {
-                    if let Some(new) = try_move(&state, &map, mode) {
+                for _ in 0..*n {
+                    if let Some(new) = try_move(&state, map, mode) {
                         state = new.clone();
                     } else {
                         break;
@@ -394,6 +414,32 @@ pub fn final_password(filename: &str, mode: i32) -> i32 {
         }
 }
 
+pub fn final_password(filename: &str, mode: i32) -> Result<i32, Error> {
+    Ok(walk(&Day22::parse_file(filename)?, mode))
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    type Input = Notes;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(notes: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(walk(notes, 0)))
+    }
+
+    fn part2(notes: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(walk(notes, 2)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::final_password;
@@ -402,12 +448,12 @@ mod tests {
     #[test_case("../testinput/day22.txt", 6032; "on test input")]
     #[test_case("../input/day22.txt", 89224; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(final_password(filename, 0), expected);
+        assert_eq!(final_password(filename, 0).unwrap(), expected);
     }
 
     #[test_case("../testinput/day22.txt", 1, 5031; "on test input")]
     #[test_case("../input/day22.txt", 2, 136_182; "on real input")]
     fn part2(filename: &str, mode: i32, expected: i32) {
-        assert_eq!(final_password(filename, mode), expected);
+        assert_eq!(final_password(filename, mode).unwrap(), expected);
     }
 }
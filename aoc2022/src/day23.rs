@@ -1,9 +1,9 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use fxhash::FxHashMap as HashMap;
 use fxhash::FxHashSet as HashSet;
-use std::fs::read_to_string;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Coord {
+pub struct Coord {
     row: i32,
     col: i32,
 }
@@ -92,21 +92,26 @@ fn print_map(map: &HashSet<Coord>) {
     println!();
 }
 
-fn parse_file(filename: &str) -> HashSet<Coord> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|(_, c)| *c == '#')
-                .map(move |(col, _)| Coord {
-                    row: i32::try_from(row).unwrap(),
-                    col: i32::try_from(col).unwrap(),
-                })
-        })
-        .collect()
+fn parse(input: &str) -> Result<HashSet<Coord>, ParseError> {
+    let mut elves = HashSet::default();
+    for (row, line) in lines(23, input).enumerate() {
+        for (col, c) in line.text.char_indices() {
+            match c {
+                '#' => {
+                    elves.insert(Coord {
+                        row: i32::try_from(row).unwrap(),
+                        col: i32::try_from(col).unwrap(),
+                    });
+                }
+                '.' => {}
+                _ => return Err(line.error_at(col, "expected '#' or '.'")),
+            }
+        }
+    }
+    if elves.is_empty() {
+        return Err(ParseError::input(23, "no elves"));
+    }
+    Ok(elves)
 }
 
 fn move_elves(elves: &HashSet<Coord>, dirindex: i32) -> HashSet<Coord> {
@@ -137,8 +142,8 @@ fn move_elves(elves: &HashSet<Coord>, dirindex: i32) -> HashSet<Coord> {
     new
 }
 
-pub fn empty_tiles(filename: &str) -> i32 {
-    let mut elves = parse_file(filename);
+fn spread_out(elves: &HashSet<Coord>) -> i32 {
+    let mut elves = elves.clone();
 
     for i in 0..10 {
         elves = move_elves(&elves, i);
@@ -151,8 +156,8 @@ pub fn empty_tiles(filename: &str) -> i32 {
     (max_row - min_row + 1) * (max_col - min_col + 1) - i32::try_from(elves.len()).unwrap()
 }
 
-pub fn no_move_round(filename: &str) -> i32 {
-    let mut elves = parse_file(filename);
+fn settle(elves: &HashSet<Coord>) -> i32 {
+    let mut elves = elves.clone();
     let mut i = 0;
     loop {
         let new = move_elves(&elves, i);
@@ -164,6 +169,36 @@ pub fn no_move_round(filename: &str) -> i32 {
     }
 }
 
+pub fn empty_tiles(filename: &str) -> Result<i32, Error> {
+    Ok(spread_out(&Day23::parse_file(filename)?))
+}
+
+pub fn no_move_round(filename: &str) -> Result<i32, Error> {
+    Ok(settle(&Day23::parse_file(filename)?))
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+
+    type Input = HashSet<Coord>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(elves: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(spread_out(elves)))
+    }
+
+    fn part2(elves: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(settle(elves)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{empty_tiles, no_move_round};
@@ -172,11 +207,11 @@ mod tests {
     #[test_case("../testinput/day23.txt", 110; "on test input")]
     #[test_case("../input/day23.txt", 3757; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(empty_tiles(filename), expected);
+        assert_eq!(empty_tiles(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day23.txt", 20; "on test input")]
     #[test_case("../input/day23.txt", 918; "on real input")]
     fn part2(filename: &str, expected: i32) {
-        assert_eq!(no_move_round(filename), expected);
+        assert_eq!(no_move_round(filename).unwrap(), expected);
     }
 }
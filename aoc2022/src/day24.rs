@@ -1,8 +1,9 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
-use std::{collections::VecDeque, fs::read_to_string};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -10,7 +11,7 @@ enum Direction {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Coord {
+pub struct Coord {
     row: usize,
     col: usize,
 }
@@ -23,30 +24,25 @@ struct QueueItem {
 
 type Blizzards = HashMap<Coord, Vec<Direction>>;
 
-fn parse_file(filename: &str) -> Blizzards {
+fn parse(input: &str) -> Result<Blizzards, ParseError> {
     let mut blizzards: Blizzards = HashMap::default();
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .enumerate()
-        .for_each(|(row, line)| {
-            line.chars().enumerate().for_each(|(col, c)| match c {
-                '^' => {
-                    blizzards.insert(Coord { row, col }, vec![Direction::Up]);
-                }
-                'v' => {
-                    blizzards.insert(Coord { row, col }, vec![Direction::Down]);
-                }
-                '<' => {
-                    blizzards.insert(Coord { row, col }, vec![Direction::Left]);
-                }
-                '>' => {
-                    blizzards.insert(Coord { row, col }, vec![Direction::Right]);
-                }
-                _ => {}
-            });
-        });
-    blizzards
+    for (row, line) in lines(24, input).enumerate() {
+        for (col, c) in line.text.char_indices() {
+            let direction = match c {
+                '^' => Direction::Up,
+                'v' => Direction::Down,
+                '<' => Direction::Left,
+                '>' => Direction::Right,
+                '#' | '.' => continue,
+                _ => return Err(line.error_at(col, "expected '#', '.' or a blizzard")),
+            };
+            blizzards.insert(Coord { row, col }, vec![direction]);
+        }
+    }
+    if blizzards.is_empty() {
+        return Err(ParseError::input(24, "no blizzards"));
+    }
+    Ok(blizzards)
 }
 
 fn move_blizzards(blizzards: &Blizzards, max: &Coord) -> Blizzards {
@@ -136,8 +132,9 @@ fn enqueue(queue: &mut VecDeque<QueueItem>, visited: &mut HashSet<QueueItem>, it
 }
 
 #[allow(clippy::too_many_lines)]
-pub fn minutes(filename: &str, legs: usize) -> usize {
-    let mut timeline: Vec<Blizzards> = vec![parse_file(filename)];
+/// Minutes to cross the valley `legs` times, turning back at each end.
+pub fn crossing(blizzards: &Blizzards, legs: usize) -> usize {
+    let mut timeline: Vec<Blizzards> = vec![blizzards.clone()];
     let max = Coord {
         row: timeline[0].keys().map(|c| c.row).max().unwrap(),
         col: timeline[0].keys().map(|c| c.col).max().unwrap(),
@@ -295,6 +292,32 @@ pub fn minutes(filename: &str, legs: usize) -> usize {
     panic!("no valid moves");
 }
 
+pub fn minutes(filename: &str, legs: usize) -> Result<usize, Error> {
+    Ok(crossing(&Day24::parse_file(filename)?, legs))
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    type Input = Blizzards;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(blizzards: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(crossing(blizzards, 1)))
+    }
+
+    fn part2(blizzards: &Self::Input) -> Result<Option<usize>, String> {
+        Ok(Some(crossing(blizzards, 3)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::minutes;
@@ -303,11 +326,11 @@ mod tests {
     #[test_case("../testinput/day24.txt", 18; "on test input")]
     #[test_case("../input/day24.txt", 281; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(minutes(filename, 1), expected);
+        assert_eq!(minutes(filename, 1).unwrap(), expected);
     }
     #[test_case("../testinput/day24.txt", 54; "on test input")]
     #[test_case("../input/day24.txt", 807; "on real input")]
     fn part2(filename: &str, expected: usize) {
-        assert_eq!(minutes(filename, 3), expected);
+        assert_eq!(minutes(filename, 3).unwrap(), expected);
     }
 }
@@ -1,19 +1,25 @@
-use std::fs::read_to_string;
+use crate::{
+    input::{lines, Line},
+    Error, ParseError, Solution,
+};
 
-fn snafu_to_int(snafu: &str) -> i64 {
-    let mut int = 0;
-    for c in snafu.chars() {
-        int *= 5;
-        int += match c {
+fn snafu_to_int(line: &Line) -> Result<i64, ParseError> {
+    let mut int: i64 = 0;
+    for (pos, c) in line.text.char_indices() {
+        let digit = match c {
             '=' => -2,
             '-' => -1,
             '0' => 0,
             '1' => 1,
             '2' => 2,
-            _ => panic!("illegal char '{}'", c),
-        }
+            _ => return Err(line.error_at(pos, "expected a SNAFU digit")),
+        };
+        int = int
+            .checked_mul(5)
+            .and_then(|int| int.checked_add(digit))
+            .ok_or_else(|| line.error(line.text, "number too large"))?;
     }
-    int
+    Ok(int)
 }
 
 fn int_to_snafu(i: i64) -> String {
@@ -40,24 +46,49 @@ fn int_to_snafu(i: i64) -> String {
     chars.iter().rev().collect()
 }
 
-pub fn sum_snafu(filename: &str) -> String {
-    int_to_snafu(
-        read_to_string(filename)
-            .unwrap()
-            .lines()
-            .map(snafu_to_int)
-            .sum(),
-    )
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    lines(25, input).map(|line| snafu_to_int(&line)).collect()
+}
+
+pub fn sum_snafu(filename: &str) -> Result<String, Error> {
+    Ok(int_to_snafu(Day25::parse_file(filename)?.iter().sum()))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+
+    type Input = Vec<i64>;
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<Option<String>, String> {
+        Ok(Some(int_to_snafu(numbers.iter().sum())))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sum_snafu;
+    use super::{parse, sum_snafu};
     use test_case::test_case;
 
     #[test_case("../testinput/day25.txt", "2=-1=0"; "on test input")]
     #[test_case("../input/day25.txt", "2=20---01==222=0=0-2"; "on real input")]
     fn part1(filename: &str, expected: &str) {
-        assert_eq!(sum_snafu(filename), expected);
+        assert_eq!(sum_snafu(filename).unwrap(), expected);
+    }
+
+    #[test]
+    fn illegal_digit() {
+        let err = parse("1=-0\n12=3\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 25, line 2, column 4: expected a SNAFU digit, found '3'"
+        );
     }
 }
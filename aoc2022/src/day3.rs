@@ -1,5 +1,35 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
-use std::fs::read_to_string;
+
+fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    let rucksacks = lines(3, input)
+        .map(|line| {
+            let half = line.text.len() / 2;
+            if let Some(i) = line.text.find(|c: char| !c.is_ascii_alphabetic()) {
+                Err(line.error_at(i, "expected an item type a-z or A-Z"))
+            } else if line.text.len() % 2 == 1 {
+                Err(line.error(line.text, "expected an even number of items"))
+            } else if common_chars(&line.text[..half], &line.text[half..]).len() != 1 {
+                Err(line.error(line.text, "expected one item type in both compartments"))
+            } else {
+                Ok(line.text.to_string())
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if rucksacks.len() % 3 != 0 {
+        return Err(ParseError::input(3, "expected groups of three elves"));
+    }
+    if let Some(group) = rucksacks
+        .chunks(3)
+        .position(|group| badge(group).len() != 1)
+    {
+        return Err(ParseError::input(
+            3,
+            format!("expected one badge for group {}", group + 1),
+        ));
+    }
+    Ok(rucksacks)
+}
 
 fn common_chars(s1: &str, s2: &str) -> String {
     s1.chars()
@@ -9,6 +39,11 @@ fn common_chars(s1: &str, s2: &str) -> String {
         .collect()
 }
 
+/// The item types all three elves of `group` carry.
+fn badge(group: &[String]) -> String {
+    common_chars(&common_chars(&group[0], &group[1]), &group[2])
+}
+
 fn priority(s: &str) -> u32 {
     assert!(s.len() == 1, "Not exactly one common char");
     match s.chars().next() {
@@ -18,45 +53,74 @@ fn priority(s: &str) -> u32 {
     }
 }
 
-pub fn rucksack(filename: &str) -> u32 {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+fn misplaced(rucksacks: &[String]) -> u32 {
+    rucksacks
+        .iter()
         .map(|line| {
             let l = line.len() / 2;
-            priority(&common_chars(&line[(..l)], &line[(l..)]))
+            priority(&common_chars(&line[..l], &line[l..]))
         })
         .sum()
 }
 
-pub fn badges(filename: &str) -> u32 {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .collect_vec()
+fn group_badges(rucksacks: &[String]) -> u32 {
+    rucksacks
         .chunks(3)
-        .map(|chunk| {
-            priority(&common_chars(
-                &common_chars(chunk.first().unwrap(), chunk.get(1).unwrap()),
-                chunk.last().unwrap(),
-            ))
-        })
+        .map(|group| priority(&badge(group)))
         .sum()
 }
 
+pub fn rucksack(filename: &str) -> Result<u32, Error> {
+    Ok(misplaced(&Day3::parse_file(filename)?))
+}
+
+pub fn badges(filename: &str) -> Result<u32, Error> {
+    Ok(group_badges(&Day3::parse_file(filename)?))
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(rucksacks: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(misplaced(rucksacks)))
+    }
+
+    fn part2(rucksacks: &Self::Input) -> Result<Option<u32>, String> {
+        Ok(Some(group_badges(rucksacks)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day3::{badges, rucksack};
+    use crate::day3::{badges, parse, rucksack};
     use test_case::test_case;
 
+    #[test_case("abcabc\n", "day 3, line 1, column 1: expected one item type in both compartments, found 'abcabc'"; "two shared")]
+    #[test_case("abcd\n", "day 3, line 1, column 1: expected one item type in both compartments, found 'abcd'"; "none shared")]
+    #[test_case("aa\nbb\n", "day 3: expected groups of three elves"; "incomplete group")]
+    #[test_case("aa\nbb\ncc\n", "day 3: expected one badge for group 1"; "no badge")]
+    fn invalid(input: &str, expected: &str) {
+        assert_eq!(parse(input).unwrap_err().to_string(), expected);
+    }
+
     #[test_case("../testinput/day3.txt", 157; "on test input")]
     #[test_case("../input/day3.txt", 7826; "on real input")]
     fn part1(filename: &str, expected: u32) {
-        assert_eq!(rucksack(filename), expected);
+        assert_eq!(rucksack(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day3.txt", 70; "on test input")]
     #[test_case("../input/day3.txt", 2577; "on real input")]
     fn part2(filename: &str, expected: u32) {
-        assert_eq!(badges(filename), expected);
+        assert_eq!(badges(filename).unwrap(), expected);
     }
 }
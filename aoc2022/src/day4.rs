@@ -1,44 +1,73 @@
-use std::fs::read_to_string;
+use crate::{input::lines, Error, ParseError, Solution};
 
-struct Range {
+pub struct Range {
     first: i32,
     last: i32,
 }
 
-fn helper<F>(filename: &str, f: F) -> u64
-where
-    F: Fn(Range, Range) -> bool,
-{
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+fn parse(input: &str) -> Result<Vec<(Range, Range)>, ParseError> {
+    lines(4, input)
         .map(|line| {
-            let mut pair = line.split(',').map(|range| {
-                let mut sections = range.split('-').map(|i| i.parse::<i32>().unwrap());
-                Range {
-                    first: sections.next().unwrap(),
-                    last: sections.next().unwrap(),
-                }
-            });
-            f(pair.next().unwrap(), pair.next().unwrap())
+            let [a, b] = line
+                .split(line.text, ",")?
+                .map(|range| -> Result<Range, ParseError> {
+                    let [first, last] = line.split(range, "-")?;
+                    Ok(Range {
+                        first: line.number(first)?,
+                        last: line.number(last)?,
+                    })
+                });
+            Ok((a?, b?))
         })
-        .filter(|b| *b)
-        .count() as u64
+        .collect()
+}
+
+fn helper<F>(pairs: &[(Range, Range)], f: F) -> u64
+where
+    F: Fn(&Range, &Range) -> bool,
+{
+    pairs.iter().filter(|(a, b)| f(a, b)).count() as u64
+}
+
+fn is_fully_contained(a: &Range, b: &Range) -> bool {
+    (a.first <= b.first && a.last >= b.last) || (a.first >= b.first && a.last <= b.last)
+}
+
+fn is_overlapping(a: &Range, b: &Range) -> bool {
+    (a.first >= b.first && a.first <= b.last)
+        || (a.last >= b.first && a.last <= b.last)
+        || (a.first <= b.first && a.last >= b.last)
+        || (a.first >= b.first && a.last <= b.last)
 }
 
-pub fn fully_contain(filename: &str) -> u64 {
-    helper(filename, |a, b| {
-        (a.first <= b.first && a.last >= b.last) || (a.first >= b.first && a.last <= b.last)
-    })
+pub fn fully_contain(filename: &str) -> Result<u64, Error> {
+    Ok(helper(&Day4::parse_file(filename)?, is_fully_contained))
 }
 
-pub fn overlap(filename: &str) -> u64 {
-    helper(filename, |a, b| {
-        (a.first >= b.first && a.first <= b.last)
-            || (a.last >= b.first && a.last <= b.last)
-            || (a.first <= b.first && a.last >= b.last)
-            || (a.first >= b.first && a.last <= b.last)
-    })
+pub fn overlap(filename: &str) -> Result<u64, Error> {
+    Ok(helper(&Day4::parse_file(filename)?, is_overlapping))
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Input = Vec<(Range, Range)>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(pairs: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(helper(pairs, is_fully_contained)))
+    }
+
+    fn part2(pairs: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(helper(pairs, is_overlapping)))
+    }
 }
 
 #[cfg(test)]
@@ -49,11 +78,11 @@ mod tests {
     #[test_case("../testinput/day4.txt", 2; "on test input")]
     #[test_case("../input/day4.txt", 538; "on real input")]
     fn part1(filename: &str, expected: u64) {
-        assert_eq!(fully_contain(filename), expected);
+        assert_eq!(fully_contain(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day4.txt", 4; "on test input")]
     #[test_case("../input/day4.txt", 792; "on real input")]
     fn part2(filename: &str, expected: u64) {
-        assert_eq!(overlap(filename), expected);
+        assert_eq!(overlap(filename).unwrap(), expected);
     }
 }
@@ -1,16 +1,24 @@
-use itertools::Itertools;
-use std::fs::read_to_string;
+use crate::{input::lines, Error, ParseError, Solution};
 
-fn crates<F>(filename: &str, mover: F) -> String
-where
-    F: Fn(Vec<Vec<char>>, i8, usize, usize) -> Vec<Vec<char>>,
-{
+pub struct Move {
+    n: i8,
+    src: usize,
+    dst: usize,
+}
+
+pub struct Cargo {
+    stacks: Vec<Vec<char>>,
+    moves: Vec<Move>,
+}
+
+fn parse(input: &str) -> Result<Cargo, ParseError> {
     let mut stacks = Vec::<Vec<char>>::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        if line.contains('[') {
-            for e in line.chars().enumerate() {
+    let mut moves = Vec::<Move>::new();
+    for line in lines(5, input) {
+        if line.text.contains('[') {
+            for e in line.text.chars().enumerate() {
                 match e {
-                    (i, c) if ('A'..='Z').contains(&c) => {
+                    (i, c) if c.is_ascii_uppercase() => {
                         while stacks.len() <= i / 4 {
                             stacks.push(Vec::<char>::new());
                         }
@@ -19,38 +27,78 @@ where
                     _ => (),
                 }
             }
-        } else if line.starts_with("move") {
-            let tokens = line.split(' ').collect_vec();
-            stacks = mover(
-                stacks,
-                tokens[1].parse::<i8>().unwrap(),
-                tokens[3].parse::<usize>().unwrap(),
-                tokens[5].parse::<usize>().unwrap(),
-            );
+        } else if line.text.starts_with("move") {
+            let [n, src, dst] = line.scan("move {} from {} to {}")?;
+            let stack = |token| match line.number(token)? {
+                no @ 1.. if no <= stacks.len() => Ok(no),
+                _ => Err(line.error(token, "no such stack")),
+            };
+            moves.push(Move {
+                n: line.number(n)?,
+                src: stack(src)?,
+                dst: stack(dst)?,
+            });
         }
     }
+    Ok(Cargo { stacks, moves })
+}
+
+fn crates<F>(cargo: &Cargo, mover: F) -> String
+where
+    F: Fn(Vec<Vec<char>>, i8, usize, usize) -> Vec<Vec<char>>,
+{
+    let mut stacks = cargo.stacks.clone();
+    for m in &cargo.moves {
+        stacks = mover(stacks, m.n, m.src, m.dst);
+    }
     stacks.iter().map(|s| s.last().unwrap()).collect::<String>()
 }
 
-pub fn crates_single(filename: &str) -> String {
-    crates(filename, |mut stacks, n, src, dst| -> Vec<Vec<char>> {
-        for _ in 0..n {
-            let c = stacks[src - 1].pop().unwrap();
-            stacks[dst - 1].push(c);
-        }
-        stacks
-    })
+fn move_single(mut stacks: Vec<Vec<char>>, n: i8, src: usize, dst: usize) -> Vec<Vec<char>> {
+    for _ in 0..n {
+        let c = stacks[src - 1].pop().unwrap();
+        stacks[dst - 1].push(c);
+    }
+    stacks
 }
 
-pub fn crates_multiple(filename: &str) -> String {
-    crates(filename, |mut stacks, n, src, dst| -> Vec<Vec<char>> {
-        let mut tmp = Vec::<char>::new();
-        for _ in 0..n {
-            tmp.insert(0, stacks[src - 1].pop().unwrap());
-        }
-        stacks[dst - 1].extend(tmp);
-        stacks
-    })
+fn move_multiple(mut stacks: Vec<Vec<char>>, n: i8, src: usize, dst: usize) -> Vec<Vec<char>> {
+    let mut tmp = Vec::<char>::new();
+    for _ in 0..n {
+        tmp.insert(0, stacks[src - 1].pop().unwrap());
+    }
+    stacks[dst - 1].extend(tmp);
+    stacks
+}
+
+pub fn crates_single(filename: &str) -> Result<String, Error> {
+    Ok(crates(&Day5::parse_file(filename)?, move_single))
+}
+
+pub fn crates_multiple(filename: &str) -> Result<String, Error> {
+    Ok(crates(&Day5::parse_file(filename)?, move_multiple))
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Cargo;
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(cargo: &Self::Input) -> Result<Option<String>, String> {
+        Ok(Some(crates(cargo, move_single)))
+    }
+
+    fn part2(cargo: &Self::Input) -> Result<Option<String>, String> {
+        Ok(Some(crates(cargo, move_multiple)))
+    }
 }
 
 #[cfg(test)]
@@ -61,11 +109,11 @@ mod tests {
     #[test_case("../testinput/day5.txt", "CMZ"; "on test input")]
     #[test_case("../input/day5.txt", "VRWBSFZWM"; "on real input")]
     fn part1(filename: &str, expected: &str) {
-        assert_eq!(crates_single(filename), expected);
+        assert_eq!(crates_single(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day5.txt", "MCD"; "on test input")]
     #[test_case("../input/day5.txt", "RBTWJWMCF"; "on real input")]
     fn part2(filename: &str, expected: &str) {
-        assert_eq!(crates_multiple(filename), expected);
+        assert_eq!(crates_multiple(filename).unwrap(), expected);
     }
 }
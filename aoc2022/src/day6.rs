@@ -1,39 +1,89 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
-use std::fs::read_to_string;
 
-fn start_of_sequence(filename: &str, size: usize) -> usize {
-    read_to_string(filename)
-        .unwrap()
-        .chars()
-        .collect_vec()
+fn parse(input: &str) -> Result<Vec<char>, ParseError> {
+    let first = lines(6, input).next().map(|line| line.trim());
+    let Some(line) = first.filter(|line| !line.text.is_empty()) else {
+        return Err(ParseError::input(6, "no signal"));
+    };
+    if let Some(i) = line.text.find(|c: char| !c.is_ascii_lowercase()) {
+        return Err(line.error_at(i, "expected a letter a-z"));
+    }
+    if input.trim_end().lines().count() > 1 {
+        return Err(ParseError::input(6, "expected the signal on one line"));
+    }
+    Ok(line.text.chars().collect_vec())
+}
+
+/// The number of characters up to the end of the first `size` different
+/// ones in a row.
+fn start_of_sequence(signal: &[char], size: usize) -> Result<usize, String> {
+    signal
         .windows(size)
-        .enumerate()
-        .find(|(_, window)| window.iter().sorted().dedup().count() == size)
-        .unwrap()
-        .0
-        + size
+        .position(|window| window.iter().sorted().dedup().count() == size)
+        .map(|i| i + size)
+        .ok_or_else(|| format!("no {size} different characters in a row"))
 }
 
-pub fn start_of_package(filename: &str) -> usize {
-    start_of_sequence(filename, 4)
+pub fn start_of_package(filename: &str) -> Result<Result<usize, String>, Error> {
+    Ok(start_of_sequence(&Day6::parse_file(filename)?, 4))
+}
+pub fn start_of_message(filename: &str) -> Result<Result<usize, String>, Error> {
+    Ok(start_of_sequence(&Day6::parse_file(filename)?, 14))
 }
-pub fn start_of_message(filename: &str) -> usize {
-    start_of_sequence(filename, 14)
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Input = Vec<char>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(signal: &Self::Input) -> Result<Option<usize>, String> {
+        start_of_sequence(signal, 4).map(Some)
+    }
+
+    fn part2(signal: &Self::Input) -> Result<Option<usize>, String> {
+        start_of_sequence(signal, 14).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day6::{start_of_message, start_of_package};
+    use crate::day6::{parse, start_of_message, start_of_package, start_of_sequence};
     use test_case::test_case;
 
+    #[test_case("", "day 6: no signal"; "empty")]
+    #[test_case("abcD\n", "day 6, line 1, column 4: expected a letter a-z, found 'D'"; "uppercase")]
+    #[test_case("abcd\nefgh\n", "day 6: expected the signal on one line"; "two lines")]
+    fn invalid(input: &str, expected: &str) {
+        assert_eq!(parse(input).unwrap_err().to_string(), expected);
+    }
+
+    #[test]
+    fn no_marker() {
+        let signal = parse("abcabcabc\n").unwrap();
+        assert_eq!(start_of_sequence(&signal, 3), Ok(3));
+        assert_eq!(
+            start_of_sequence(&signal, 4),
+            Err("no 4 different characters in a row".to_string())
+        );
+    }
+
     #[test_case("../testinput/day6.txt", 7; "on test input")]
     #[test_case("../input/day6.txt", 1804; "on real input")]
     fn part1(filename: &str, expected: usize) {
-        assert_eq!(start_of_package(filename), expected);
+        assert_eq!(start_of_package(filename).unwrap(), Ok(expected));
     }
     #[test_case("../testinput/day6.txt", 19; "on test input")]
     #[test_case("../input/day6.txt", 2508; "on real input")]
     fn part2(filename: &str, expected: usize) {
-        assert_eq!(start_of_message(filename), expected);
+        assert_eq!(start_of_message(filename).unwrap(), Ok(expected));
     }
 }
@@ -1,19 +1,21 @@
+use crate::{input::lines, Error, ParseError, Solution};
 use itertools::Itertools;
-use std::{collections::HashMap, fs::read_to_string};
+use std::collections::HashMap;
 
-fn dirsizes(filename: &str) -> HashMap<String, i32> {
+fn dirsizes(input: &str) -> Result<HashMap<String, i32>, ParseError> {
     let mut cwd = vec![""];
     let mut dirsizes = HashMap::<String, i32>::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        if line.starts_with("$ cd ") {
-            match line.get(5..) {
-                Some("/") => cwd = vec![""],
-                Some("..") => _ = cwd.pop(),
-                Some(dir) => cwd.push(dir),
-                None => panic!("impossible cd"),
+    for line in lines(7, input) {
+        if let Some(dir) = line.text.strip_prefix("$ cd ") {
+            match dir {
+                "/" => cwd = vec![""],
+                ".." if cwd.len() > 1 => _ = cwd.pop(),
+                ".." => return Err(line.error(dir, "already at the root directory")),
+                dir => cwd.push(dir),
             }
-        } else if line.chars().next().unwrap().is_numeric() {
-            let size = line.split(' ').next().unwrap().parse::<i32>().unwrap();
+        } else if line.text != "$ ls" && !line.text.starts_with("dir ") {
+            let [size, _] = line.split(line.text, " ")?;
+            let size = line.number::<i32>(size)?;
             for i in 0..cwd.len() {
                 dirsizes
                     .entry(cwd.get(..=i).unwrap().join("/"))
@@ -22,25 +24,55 @@ fn dirsizes(filename: &str) -> HashMap<String, i32> {
             }
         }
     }
-    dirsizes
+    if dirsizes.is_empty() {
+        return Err(ParseError::input(7, "no files listed"));
+    }
+    Ok(dirsizes)
 }
 
-pub fn sum_dirs_below(filename: &str) -> i32 {
-    dirsizes(filename)
-        .into_values()
-        .filter(|i| *i <= 100_000)
-        .sum()
+fn small_dirs(dirs: &HashMap<String, i32>) -> i32 {
+    dirs.values().filter(|i| **i <= 100_000).sum()
 }
 
-pub fn dir_to_delete(filename: &str) -> i32 {
-    let dirs = dirsizes(filename);
+fn smallest_to_delete(dirs: &HashMap<String, i32>) -> i32 {
     let needed = 30_000_000 - 70_000_000 + dirs[""];
-    dirs.into_values()
+    dirs.values()
+        .copied()
         .sorted()
         .find(|size| *size >= needed)
         .unwrap()
 }
 
+pub fn sum_dirs_below(filename: &str) -> Result<i32, Error> {
+    Ok(small_dirs(&Day7::parse_file(filename)?))
+}
+
+pub fn dir_to_delete(filename: &str) -> Result<i32, Error> {
+    Ok(smallest_to_delete(&Day7::parse_file(filename)?))
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Input = HashMap<String, i32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        dirsizes(input)
+    }
+
+    fn part1(dirs: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(small_dirs(dirs)))
+    }
+
+    fn part2(dirs: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(smallest_to_delete(dirs)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::day7::{dir_to_delete, sum_dirs_below};
@@ -49,11 +81,11 @@ mod tests {
     #[test_case("../testinput/day7.txt", 95_437; "on test input")]
     #[test_case("../input/day7.txt", 1_792_222; "on real input")]
     fn part1(filename: &str, expected: i32) {
-        assert_eq!(sum_dirs_below(filename), expected);
+        assert_eq!(sum_dirs_below(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day7.txt", 24_933_642; "on test input")]
     #[test_case("../input/day7.txt", 1_112_963; "on real input")]
     fn part2(filename: &str, expected: i32) {
-        assert_eq!(dir_to_delete(filename), expected);
+        assert_eq!(dir_to_delete(filename).unwrap(), expected);
     }
 }
@@ -1,14 +1,26 @@
-use itertools::Itertools;
-use std::fs::read_to_string;
+use crate::{input::lines, Error, ParseError, Solution};
 
 type Grid = Vec<Vec<u32>>;
 
-fn parse_file(filename: &str) -> Grid {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.chars().map(|d| d.to_digit(10).unwrap()).collect_vec())
-        .collect_vec()
+fn parse(input: &str) -> Result<Grid, ParseError> {
+    let grid: Grid = lines(8, input)
+        .map(|line| {
+            line.text
+                .char_indices()
+                .map(|(i, d)| {
+                    d.to_digit(10)
+                        .ok_or_else(|| line.error_at(i, "expected a digit"))
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    if grid.is_empty() {
+        return Err(ParseError::input(8, "no trees"));
+    }
+    if grid.iter().any(|row| row.len() != grid.len()) {
+        return Err(ParseError::input(8, "expected a square grid"));
+    }
+    Ok(grid)
 }
 
 fn is_visible(x: usize, y: usize, grid: &Grid) -> bool {
@@ -97,36 +109,70 @@ fn score(x: usize, y: usize, grid: &Grid) -> u64 {
     left * right * up * down
 }
 
-pub fn visible(filename: &str) -> u64 {
-    let grid = parse_file(filename);
+fn count_visible(grid: &Grid) -> u64 {
     let size = grid.len();
     (0..size)
-        .map(|x| (0..size).filter(|y| is_visible(x, *y, &grid)).count() as u64)
+        .map(|x| (0..size).filter(|y| is_visible(x, *y, grid)).count() as u64)
         .sum()
 }
 
-pub fn highest_score(filename: &str) -> u64 {
-    let grid = parse_file(filename);
+fn best_score(grid: &Grid) -> u64 {
     let size = grid.len();
     (0..size)
-        .map(|x| (0..size).map(|y| score(x, y, &grid)).max().unwrap())
+        .map(|x| (0..size).map(|y| score(x, y, grid)).max().unwrap())
         .max()
         .unwrap()
 }
 
+pub fn visible(filename: &str) -> Result<u64, Error> {
+    Ok(count_visible(&Day8::parse_file(filename)?))
+}
+
+pub fn highest_score(filename: &str) -> Result<u64, Error> {
+    Ok(best_score(&Day8::parse_file(filename)?))
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Input = Grid;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(grid: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(count_visible(grid)))
+    }
+
+    fn part2(grid: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(best_score(grid)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day8::{highest_score, visible};
+    use crate::day8::{highest_score, parse, visible};
     use test_case::test_case;
 
+    #[test_case("", "day 8: no trees"; "empty")]
+    #[test_case("12\n3\n", "day 8: expected a square grid"; "not square")]
+    fn invalid(input: &str, expected: &str) {
+        assert_eq!(parse(input).unwrap_err().to_string(), expected);
+    }
+
     #[test_case("../testinput/day8.txt", 21; "on test input")]
     #[test_case("../input/day8.txt", 1798; "on real input")]
     fn part1(filename: &str, expected: u64) {
-        assert_eq!(visible(filename), expected);
+        assert_eq!(visible(filename).unwrap(), expected);
     }
     #[test_case("../testinput/day8.txt", 8; "on test input")]
     #[test_case("../input/day8.txt", 259_308; "on real input")]
     fn part2(filename: &str, expected: u64) {
-        assert_eq!(highest_score(filename), expected);
+        assert_eq!(highest_score(filename).unwrap(), expected);
     }
 }
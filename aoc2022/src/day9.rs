@@ -1,13 +1,14 @@
-use std::{collections::HashSet, fs::read_to_string};
+use crate::{input::lines, Error, ParseError, Solution};
+use std::collections::HashSet;
 
 #[derive(Clone, Copy, Debug)]
-enum Direction {
+pub enum Direction {
     L,
     U,
     R,
     D,
 }
-struct Motion {
+pub struct Motion {
     dir: Direction,
     steps: u32,
 }
@@ -18,25 +19,20 @@ struct Pos {
     y: i32,
 }
 
-fn parse_file(filename: &str) -> Vec<Motion> {
-    read_to_string(filename)
-        .unwrap()
-        .lines()
+fn parse(input: &str) -> Result<Vec<Motion>, ParseError> {
+    lines(9, input)
         .map(|line| {
-            let mut split = line.split(' ');
-            let dir = split.next();
-            let steps = split.next().unwrap().parse::<u32>().unwrap();
-            Motion {
+            let [dir, steps] = line.split(line.text, " ")?;
+            Ok(Motion {
                 dir: match dir {
-                    Some("L") => Direction::L,
-                    Some("U") => Direction::U,
-                    Some("R") => Direction::R,
-                    Some("D") => Direction::D,
-                    Some(_) => panic!("unknown direction"),
-                    None => panic!("parse error"),
+                    "L" => Direction::L,
+                    "U" => Direction::U,
+                    "R" => Direction::R,
+                    "D" => Direction::D,
+                    _ => return Err(line.error(dir, "expected one of L, U, R, D")),
                 },
-                steps,
-            }
+                steps: line.number(steps)?,
+            })
         })
         .collect()
 }
@@ -71,10 +67,11 @@ fn new_pos(knots: &[Pos], idx: usize) -> Pos {
 }
 
 // Imperative version is ~50 times faster than functional :-(
-pub fn visited_nodes(filename: &str, n: usize) -> u64 {
+/// Number of positions visited by the tail of a rope of `n` knots.
+pub fn simulate(motions: &[Motion], n: usize) -> u64 {
     let mut knots = vec![Pos { x: 0, y: 0 }; n];
     let mut visited = HashSet::<Pos>::new();
-    for elem in parse_file(filename) {
+    for elem in motions {
         for _ in 0..elem.steps {
             knots[0] = match elem.dir {
                 Direction::D => Pos {
@@ -103,6 +100,32 @@ pub fn visited_nodes(filename: &str, n: usize) -> u64 {
     visited.len() as u64
 }
 
+pub fn visited_nodes(filename: &str, n: usize) -> Result<u64, Error> {
+    Ok(simulate(&Day9::parse_file(filename)?, n))
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Input = Vec<Motion>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(motions: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(simulate(motions, 2)))
+    }
+
+    fn part2(motions: &Self::Input) -> Result<Option<u64>, String> {
+        Ok(Some(simulate(motions, 10)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::visited_nodes;
@@ -111,12 +134,12 @@ mod tests {
     #[test_case("../testinput/day9.txt", 13; "on test input")]
     #[test_case("../input/day9.txt", 6498; "on real input")]
     fn part1(filename: &str, expected: u64) {
-        assert_eq!(visited_nodes(filename, 2), expected);
+        assert_eq!(visited_nodes(filename, 2).unwrap(), expected);
     }
     #[test_case("../testinput/day9.txt", 1; "on test input")]
     #[test_case("../testinput/day9b.txt", 36; "on test input B")]
     #[test_case("../input/day9.txt", 2531; "on real input")]
     fn part2(filename: &str, expected: u64) {
-        assert_eq!(visited_nodes(filename, 10), expected);
+        assert_eq!(visited_nodes(filename, 10).unwrap(), expected);
     }
 }
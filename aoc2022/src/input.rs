@@ -0,0 +1,162 @@
+use crate::ParseError;
+use std::str::FromStr;
+
+/// A line of puzzle input, or a part of one, that knows where it came from
+/// so that parse errors can point at the offending text.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Line<'a> {
+    pub day: u8,
+    pub no: usize,
+    pub text: &'a str,
+    full: &'a str,
+}
+
+pub(crate) fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(move |(i, text)| Line {
+        day,
+        no: i + 1,
+        text,
+        full: text,
+    })
+}
+
+impl<'a> Line<'a> {
+    fn column(&self, token: &str) -> usize {
+        let start = self.full.as_ptr() as usize;
+        let pos = token.as_ptr() as usize;
+        if pos >= start && pos <= start + self.full.len() {
+            pos - start + 1
+        } else {
+            1
+        }
+    }
+
+    fn with_text(&self, text: &'a str) -> Self {
+        Line { text, ..*self }
+    }
+
+    /// Error located at `token`, which should be a slice of this line.
+    pub fn error(&self, token: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            day: self.day,
+            line: self.no,
+            column: self.column(token),
+            text: token.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Error located at the character starting at byte `index` of `text`.
+    pub fn error_at(&self, index: usize, message: impl Into<String>) -> ParseError {
+        let len = self.text[index..].chars().next().map_or(0, char::len_utf8);
+        self.error(&self.text[index..index + len], message)
+    }
+
+    /// Error located just past the end of `text`.
+    pub fn error_at_end(&self, message: impl Into<String>) -> ParseError {
+        self.error(&self.text[self.text.len()..], message)
+    }
+
+    pub fn trim(&self) -> Self {
+        self.with_text(self.text.trim())
+    }
+
+    pub fn number<T: FromStr>(&self, token: &str) -> Result<T, ParseError> {
+        token
+            .parse()
+            .map_err(|_| self.error(token, "expected a number"))
+    }
+
+    /// Splits `text` on `separator`, requiring exactly `N` fields.
+    pub fn split<const N: usize>(
+        &self,
+        text: &'a str,
+        separator: &str,
+    ) -> Result<[&'a str; N], ParseError> {
+        text.split(separator)
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| {
+                self.error(
+                    text,
+                    format!("expected {N} fields separated by '{separator}'"),
+                )
+            })
+    }
+
+    /// Matches the start of the line word by word against `template`, where
+    /// each of the `N` `{}` captures (part of) a word. Returns the captures
+    /// and the rest of the line.
+    pub fn scan_prefix<const N: usize>(
+        &self,
+        template: &str,
+    ) -> Result<([&'a str; N], Line<'a>), ParseError> {
+        let mut rest = self.text;
+        let mut captures = Vec::new();
+        for pattern in template.split(' ') {
+            if rest.is_empty() {
+                return Err(self
+                    .with_text(rest)
+                    .error_at_end(format!("expected '{pattern}'")));
+            }
+            let (word, tail) = rest.split_once(' ').unwrap_or((rest, &rest[rest.len()..]));
+            let mismatch = || self.error(word, format!("expected '{pattern}'"));
+            match pattern.split_once("{}") {
+                Some((prefix, suffix)) => {
+                    match word
+                        .strip_prefix(prefix)
+                        .and_then(|w| w.strip_suffix(suffix))
+                    {
+                        Some(capture) if !capture.is_empty() => captures.push(capture),
+                        _ => return Err(mismatch()),
+                    }
+                }
+                None if word == pattern => {}
+                None => return Err(mismatch()),
+            }
+            rest = tail;
+        }
+        let captures = captures
+            .try_into()
+            .unwrap_or_else(|_| panic!("template '{template}' does not capture {N} fields"));
+        Ok((captures, self.with_text(rest)))
+    }
+
+    /// Like [`Line::scan_prefix`], but the template must cover the whole line.
+    pub fn scan<const N: usize>(&self, template: &str) -> Result<[&'a str; N], ParseError> {
+        let (captures, rest) = self.scan_prefix(template)?;
+        if rest.text.is_empty() {
+            Ok(captures)
+        } else {
+            Err(rest.error(rest.text, "unexpected trailing text"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lines;
+
+    #[test]
+    fn scan_reports_position_of_mismatch() {
+        let line = lines(19, "\n\nBlueprint 1: Each ore robot costs 4 clay.")
+            .nth(2)
+            .unwrap();
+        let err = line
+            .scan::<2>("Blueprint {}: Each ore robot costs {} ore.")
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (3, 37));
+        assert_eq!(
+            err.to_string(),
+            "day 19, line 3, column 37: expected 'ore.', found 'clay.'"
+        );
+    }
+
+    #[test]
+    fn scan_captures() {
+        let line = lines(15, "Sensor at x=2, y=-18").next().unwrap();
+        let [x, y] = line.scan("Sensor at x={}, y={}").unwrap();
+        assert_eq!((x, y), ("2", "-18"));
+        assert_eq!(line.number::<i64>(y), Ok(-18));
+    }
+}
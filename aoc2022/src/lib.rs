@@ -1,7 +1,9 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::must_use_candidate)]
+#![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 
+pub mod circle;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -27,3 +29,113 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+mod error;
+mod input;
+mod solution;
+
+pub use error::{Error, ParseError};
+pub use solution::{Parsed, Puzzle, Solution};
+
+/// All implemented days, in calendar order.
+pub fn puzzles() -> Vec<Puzzle> {
+    vec![
+        Puzzle::new::<day1::Day1>(),
+        Puzzle::new::<day2::Day2>(),
+        Puzzle::new::<day3::Day3>(),
+        Puzzle::new::<day4::Day4>(),
+        Puzzle::new::<day5::Day5>(),
+        Puzzle::new::<day6::Day6>(),
+        Puzzle::new::<day7::Day7>(),
+        Puzzle::new::<day8::Day8>(),
+        Puzzle::new::<day9::Day9>(),
+        Puzzle::new::<day10::Day10>(),
+        Puzzle::new::<day11::Day11>(),
+        Puzzle::new::<day12::Day12>(),
+        Puzzle::new::<day13::Day13>(),
+        Puzzle::new::<day14::Day14>(),
+        Puzzle::new::<day15::Day15>(),
+        Puzzle::new::<day16::Day16>(),
+        Puzzle::new::<day17::Day17>(),
+        Puzzle::new::<day18::Day18>(),
+        Puzzle::new::<day19::Day19>(),
+        Puzzle::new::<day20::Day20>(),
+        Puzzle::new::<day21::Day21>(),
+        Puzzle::new::<day22::Day22>(),
+        Puzzle::new::<day23::Day23>(),
+        Puzzle::new::<day24::Day24>(),
+        Puzzle::new::<day25::Day25>(),
+    ]
+}
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    match day.trim().parse() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(format!("invalid day '{day}', expected 1 to 25")),
+    }
+}
+
+/// Parses a list of days and ranges like `3,5,10-12` into days in calendar
+/// order, each once.
+pub fn parse_days(spec: &str) -> Result<Vec<u8>, String> {
+    let mut days = Vec::new();
+    for item in spec.split(',') {
+        if let Some((first, last)) = item.split_once('-') {
+            let (first, last) = (parse_day(first)?, parse_day(last)?);
+            if first > last {
+                return Err(format!("invalid range '{item}'"));
+            }
+            days.extend(first..=last);
+        } else {
+            days.push(parse_day(item)?);
+        }
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+/// Looks up a single day in [`puzzles`].
+pub fn puzzle(day: u8) -> Option<Puzzle> {
+    puzzles().into_iter().find(|puzzle| puzzle.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{day6::Day6, parse_days, puzzle, puzzles, Solution};
+
+    #[test]
+    fn registry_in_calendar_order() {
+        let days: Vec<u8> = puzzles().iter().map(|puzzle| puzzle.day).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(parse_days("10-12,3,11"), Ok(vec![3, 10, 11, 12]));
+        assert_eq!(
+            parse_days("12-10"),
+            Err("invalid range '12-10'".to_string())
+        );
+        assert_eq!(
+            parse_days("0"),
+            Err("invalid day '0', expected 1 to 25".to_string())
+        );
+    }
+
+    #[test]
+    fn solve_from_text() {
+        let signal = Day6::parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap();
+        assert_eq!(Day6::part1(&signal), Ok(Some(7)));
+        assert_eq!(Day6::part2(&signal), Ok(Some(19)));
+    }
+
+    #[test]
+    fn solve_from_reader() {
+        let parsed = puzzle(1)
+            .unwrap()
+            .parse_reader("1000\n2000\n\n4000\n\n500\n".as_bytes())
+            .unwrap();
+        assert_eq!(parsed.part1(), Ok(Some("4000".to_string())));
+        assert_eq!(parsed.part2(), Ok(Some("7500".to_string())));
+    }
+}
@@ -1,9 +1,35 @@
-use std::fs::read_to_string;
+use crate::{input::lines, Error, ParseError, Solution};
 
-pub fn impl1() -> i32 {
+fn parse(input: &str) -> Result<Vec<i32>, ParseError> {
+    lines(0, input).map(|line| line.number(line.text)).collect()
+}
+
+fn solve1(_input: &[i32]) -> i32 {
     0
 }
 
+pub fn impl1(filename: &str) -> Result<i32, Error> {
+    Ok(solve1(&DayX::parse_file(filename)?))
+}
+
+pub struct DayX;
+
+impl Solution for DayX {
+    const DAY: u8 = 0;
+
+    type Input = Vec<i32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Option<i32>, String> {
+        Ok(Some(solve1(input)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::impl1;
@@ -11,7 +37,7 @@ mod tests {
 
     #[test_case("../testinput/dayX.txt", 0; "on test input")]
     // #[test_case("../input/dayX.txt", 0; "on real input")]
-    fn part1(filename: &str, expected: u64) {
-        assert_eq!(impl1(filename), expected);
+    fn part1(filename: &str, expected: i32) {
+        assert_eq!(impl1(filename).unwrap(), expected);
     }
 }
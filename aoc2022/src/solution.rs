@@ -0,0 +1,96 @@
+use crate::{Error, ParseError};
+use std::{fmt::Display, fs, io::Read, path::Path};
+
+/// A day of the calendar: parse the puzzle input once, then solve both parts
+/// from the parsed form.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// Parses the input, along with any preprocessing that both parts need,
+    /// so that it is done and timed only once.
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    /// Reads all of `reader`, e.g. stdin, and parses it.
+    fn parse_reader(reader: impl Read) -> Result<Self::Input, Error> {
+        Ok(Self::parse(&read_all(reader)?)?)
+    }
+
+    fn parse_file(path: impl AsRef<Path>) -> Result<Self::Input, Error> {
+        Ok(Self::parse(&fs::read_to_string(path)?)?)
+    }
+
+    /// Returns `Ok(None)` while the part is not implemented, and an error
+    /// when input that parses still has no answer, e.g. an equation without
+    /// a solution.
+    fn part1(_input: &Self::Input) -> Result<Option<Self::Answer1>, String> {
+        Ok(None)
+    }
+
+    /// Like [`Solution::part1`].
+    fn part2(_input: &Self::Input) -> Result<Option<Self::Answer2>, String> {
+        Ok(None)
+    }
+}
+
+/// Parsed input of some day, with the answer types erased to strings. Both
+/// parts may be solved at the same time from different threads.
+/// A part gives `Ok(None)` while it is not implemented and an error when
+/// the input has no answer.
+pub trait Parsed: Send + Sync {
+    fn part1(&self) -> Result<Option<String>, String>;
+    fn part2(&self) -> Result<Option<String>, String>;
+}
+
+struct Input<S: Solution>(S::Input);
+
+impl<S> Parsed for Input<S>
+where
+    S: Solution,
+    S::Input: Send + Sync,
+{
+    fn part1(&self) -> Result<Option<String>, String> {
+        Ok(S::part1(&self.0)?.map(|answer| answer.to_string()))
+    }
+
+    fn part2(&self) -> Result<Option<String>, String> {
+        Ok(S::part2(&self.0)?.map(|answer| answer.to_string()))
+    }
+}
+
+/// Registry entry for one day, see [`crate::puzzles`].
+#[derive(Clone, Copy)]
+pub struct Puzzle {
+    pub day: u8,
+    parse: fn(&str) -> Result<Box<dyn Parsed>, ParseError>,
+}
+
+impl Puzzle {
+    pub fn new<S>() -> Self
+    where
+        S: Solution + 'static,
+        S::Input: Send + Sync + 'static,
+    {
+        Puzzle {
+            day: S::DAY,
+            parse: |input| Ok(Box::new(Input::<S>(S::parse(input)?))),
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        (self.parse)(input)
+    }
+
+    pub fn parse_reader(&self, reader: impl Read) -> Result<Box<dyn Parsed>, Error> {
+        Ok(self.parse(&read_all(reader)?)?)
+    }
+}
+
+fn read_all(mut reader: impl Read) -> std::io::Result<String> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(input)
+}